@@ -5,7 +5,7 @@ files = []
 with open("./pronouns.tab") as fin:
     rdr = csv.reader(fin, delimiter="\t")
     for row in rdr:
-        nom, acc, gen, pos, ref, singular = row
+        nom, acc, gen, pos, ref, singular, agreement = row
 
         fname = f"pronouns/{nom}-{acc}-{gen}-{pos}-{ref}.dhall"
         fname = fname.replace("'", "_")
@@ -13,6 +13,8 @@ with open("./pronouns.tab") as fin:
         with open(fname, "w") as fout:
             fout.write(f"""let PronounSet = ../types/PronounSet.dhall
 
+let Number = ../types/Number.dhall
+
 in PronounSet::{{
     , nominative = "{nom}"
     , accusative = "{acc}"
@@ -20,6 +22,7 @@ in PronounSet::{{
     , possessive = "{pos}"
     , reflexive = "{ref}"
     , singular = {singular}
+    , agreement = Number.{agreement}
 }}""")
 
         files.append(fname)
@@ -0,0 +1,99 @@
+//! Compile-time checked pronoun templates for [xe_pronouns](https://github.com/Xe/pronouns).
+//! Enable the `macros` feature of `xe_pronouns` and use it from there.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Expr, LitStr, Token,
+};
+use xe_pronouns_syntax::{tokenize, Case, Placeholder, Token as Piece};
+
+struct Input {
+    template: LitStr,
+    set: Expr,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let template = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let set = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { template, set })
+    }
+}
+
+/// Format a pronoun template against a `&PronounSet` into a `String`, checking every placeholder
+/// at compile time. The template syntax is the same as `xe_pronouns::Template`:
+///
+/// ```ignore
+/// let text = pronoun_format!("{Nom} brought {det} frisbee", &set);
+/// ```
+#[proc_macro]
+pub fn pronoun_format(input: TokenStream) -> TokenStream {
+    let Input { template, set } = parse_macro_input!(input as Input);
+
+    match expand(&template, &set) {
+        Ok(tokens) => tokens.into(),
+        Err(why) => syn::Error::new(template.span(), why)
+            .to_compile_error()
+            .into(),
+    }
+}
+
+fn expand(template: &LitStr, set: &Expr) -> Result<TokenStream2, String> {
+    let source = template.value();
+    let mut pushes = Vec::new();
+
+    for token in tokenize(&source).map_err(|why| why.to_string())? {
+        let capitalised = token.capitalised();
+        let name = match token {
+            Piece::Text(text) => {
+                pushes.push(quote! { __out.push_str(#text); });
+                continue;
+            }
+            Piece::Placeholder { name, .. } => name,
+        };
+
+        let value = placeholder(name)?;
+        pushes.push(if capitalised {
+            quote! { __out.push_str(&::xe_pronouns::__private::capitalize(&#value)); }
+        } else {
+            quote! { __out.push_str(&#value); }
+        });
+    }
+
+    Ok(quote! {
+        {
+            let __set: &::xe_pronouns::PronounSet = &(#set);
+            let mut __out = ::std::string::String::new();
+            #(#pushes)*
+            __out
+        }
+    })
+}
+
+/// Turn one placeholder into an expression that can be borrowed as a `str`.
+fn placeholder(name: &str) -> Result<TokenStream2, String> {
+    Ok(
+        match Placeholder::parse(name).map_err(|why| why.to_string())? {
+            Placeholder::Form(case) => {
+                let field = match case {
+                    Case::Nominative => quote! { nominative },
+                    Case::Accusative => quote! { accusative },
+                    Case::Determiner => quote! { determiner },
+                    Case::Possessive => quote! { possessive },
+                    Case::Reflexive => quote! { reflexive },
+                };
+                quote! { __set.#field }
+            }
+            Placeholder::Contraction(verb) => {
+                quote! { ::xe_pronouns::__private::contraction(__set, #verb) }
+            }
+            Placeholder::Verb(verb) => quote! { __set.conjugate(#verb) },
+            Placeholder::Number(one, many) => quote! { if __set.singular { #one } else { #many } },
+        },
+    )
+}
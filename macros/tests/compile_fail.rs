@@ -0,0 +1,6 @@
+#[test]
+fn pronoun_format() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass.rs");
+    t.compile_fail("tests/ui/fail_*.rs");
+}
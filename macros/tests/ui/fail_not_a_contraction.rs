@@ -0,0 +1,7 @@
+use xe_pronouns::{pronoun_format, PronounSet};
+
+fn greet(set: &PronounSet) -> String {
+    pronoun_format!("{nom+go} home", set)
+}
+
+fn main() {}
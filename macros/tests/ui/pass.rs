@@ -0,0 +1,19 @@
+use xe_pronouns::{pronoun_format, PronounSet};
+
+fn main() {
+    let set = PronounSet::new(
+        "xe".to_string(),
+        "xem".to_string(),
+        "xyr".to_string(),
+        "xyrs".to_string(),
+        "xemself".to_string(),
+    );
+
+    assert_eq!(
+        pronoun_format!(
+            "{nom} {verb:go} {to|between} {ref}. {nom+be} {{here}}",
+            &set
+        ),
+        "Xe goes to xemself. Xe's {here}"
+    );
+}
@@ -0,0 +1,194 @@
+use std::cmp::Ordering;
+
+use serde::Serialize;
+
+use super::{
+    lint::COMMON_WORDS,
+    normalize,
+    rewrite::{is_apostrophe, pick_case, words},
+    trie::key_of,
+    Case, PronounSet, PronounTrie,
+};
+
+/// A form found in the text that counts towards a set.
+#[derive(Clone, Serialize, Debug, PartialEq)]
+pub struct Evidence {
+    /// Where the form starts in the text, in bytes.
+    pub start: usize,
+    /// Where the form ends in the text, in bytes.
+    pub end: usize,
+    /// The form as it was written.
+    pub form: String,
+    pub case: Case,
+    /// How much this form counted for: 1 divided by the number of sets that share it, and half
+    /// that again for forms that are also everyday words like "it".
+    pub weight: f64,
+}
+
+/// A set that a text could be using, and why.
+#[derive(Clone, Serialize, Debug)]
+pub struct Candidate {
+    pub set: PronounSet,
+    /// The total weight of the evidence.
+    pub score: f64,
+    /// Every case that was found, in order.
+    pub cases: Vec<Case>,
+    pub evidence: Vec<Evidence>,
+}
+
+impl PronounTrie {
+    /// Work out which sets some text uses, best first. Every form in the text counts towards
+    /// every set that has it, but forms that several sets share (like "hir", which ze, sie, shi
+    /// and zie all have) are split between them, so a form only one set has counts the most.
+    /// Sets with the same score are ranked by how many different cases were found.
+    pub fn analyze(&self, text: &str) -> Vec<Candidate> {
+        let mut candidates: Vec<Candidate> = Vec::new();
+        let words = words(text);
+        let adjacent = |i: usize, j: usize| text[words[i].end..words[j].start].trim().is_empty();
+
+        // Every set that has the form in one of the cases, with every case it has it in.
+        let matching = |form: &str, cases: &[Case]| -> Vec<(&PronounSet, Vec<Case>)> {
+            let mut found: Vec<(&PronounSet, Vec<Case>)> = Vec::new();
+            for &case in cases {
+                for set in self.with_form(case, form) {
+                    match found
+                        .iter_mut()
+                        .find(|(other, _)| key_of(other) == key_of(set))
+                    {
+                        Some((_, cases)) => cases.push(case),
+                        None => found.push((set, vec![case])),
+                    }
+                }
+            }
+            found
+        };
+
+        for (i, range) in words.iter().enumerate() {
+            let word = &text[range.clone()];
+            let before = (i > 0 && adjacent(i - 1, i)).then(|| &text[words[i - 1].clone()]);
+            let after =
+                (i + 1 < words.len() && adjacent(i, i + 1)).then(|| &text[words[i + 1].clone()]);
+
+            let mut found = matching(word, &Case::ALL);
+
+            // Contractions like "she's" count for the nominative in front of the apostrophe.
+            let mut form = word;
+            if found.is_empty() {
+                form = word.split(is_apostrophe).next().unwrap_or(word);
+                if form != word {
+                    found = matching(form, &[Case::Nominative]);
+                }
+            }
+
+            if found.is_empty() {
+                continue;
+            }
+
+            let common = COMMON_WORDS.contains(&&*normalize(form));
+            let weight = if common { 0.5 } else { 1.0 } / found.len() as f64;
+
+            for (set, cases) in found {
+                let case = pick_case(&cases, before, after);
+                let key = key_of(set);
+                let at = match candidates
+                    .iter()
+                    .position(|candidate| key_of(&candidate.set) == key)
+                {
+                    Some(at) => at,
+                    None => {
+                        candidates.push(Candidate {
+                            set: set.clone(),
+                            score: 0.0,
+                            cases: vec![],
+                            evidence: vec![],
+                        });
+                        candidates.len() - 1
+                    }
+                };
+                let candidate = &mut candidates[at];
+
+                candidate.score += weight;
+                if let Err(at) = candidate.cases.binary_search(&case) {
+                    candidate.cases.insert(at, case);
+                }
+                candidate.evidence.push(Evidence {
+                    start: range.start,
+                    end: range.start + form.len(),
+                    form: form.to_string(),
+                    case,
+                    weight,
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(b.cases.len().cmp(&a.cases.len()))
+                .then_with(|| key_of(&a.set).cmp(&key_of(&b.set)))
+        });
+
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(candidates: &[Candidate]) -> Vec<String> {
+        candidates
+            .iter()
+            .map(|candidate| candidate.set.url())
+            .collect()
+    }
+
+    #[test]
+    fn a_shared_form_alone_ties() {
+        let trie = crate::test_trie();
+        let candidates = trie.analyze("I saw hir.");
+
+        assert_eq!(
+            urls(&candidates),
+            [
+                "/shi/hir/hir/hirs/hirself",
+                "/sie/hir/hir/hirs/hirself",
+                "/ze/hir/hir/hirs/hirself",
+                "/zie/hir/hir/hirs/hirself",
+            ]
+        );
+        for candidate in &candidates {
+            assert_eq!(candidate.score, 0.25);
+            assert_eq!(candidate.cases, [Case::Accusative]);
+            assert_eq!(candidate.evidence[0].start..candidate.evidence[0].end, 6..9);
+        }
+    }
+
+    #[test]
+    fn another_form_breaks_the_tie() {
+        let trie = crate::test_trie();
+        let candidates = trie.analyze("Sie saw hir.");
+
+        assert_eq!(candidates[0].set.url(), "/sie/hir/hir/hirs/hirself");
+        assert_eq!(candidates[0].score, 1.25);
+        assert_eq!(candidates[0].cases, [Case::Nominative, Case::Accusative]);
+        assert!(candidates[1..]
+            .iter()
+            .all(|candidate| candidate.score < 1.0));
+    }
+
+    #[test]
+    fn contractions_and_common_words() {
+        let trie = crate::test_trie();
+
+        let candidates = trie.analyze("Xe\u{2019}s here.");
+        assert_eq!(candidates[0].set.nominative, "xe");
+        assert_eq!(candidates[0].evidence[0].form, "Xe");
+        assert_eq!(candidates[0].evidence[0].end, 2);
+
+        let it = trie.analyze("I like it.");
+        assert!(it.iter().all(|candidate| candidate.score <= 0.5));
+        assert!(trie.analyze("Nothing to see.").is_empty());
+    }
+}
@@ -0,0 +1,469 @@
+//! A language server that flags pronouns that don't match the people in a document. People are
+//! declared in the document's front matter:
+//!
+//! ```text
+//! ---
+//! pronouns:
+//!   Alex: xe/xem
+//!   Sam: she/her
+//! ---
+//! ```
+//!
+//! Run it with the path to the pronoun database (./dhall/package.dhall by default) and point an
+//! editor at its stdin and stdout.
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use serde_json::{json, Value};
+use xe_pronouns::{Character, Diagnostic, PronounSet, PronounTrie, Severity};
+
+/// Someone declared in the front matter, along with where the declaration is.
+struct Declared {
+    name: String,
+    pronoun: String,
+    start: usize,
+    end: usize,
+}
+
+/// An open document and what was last found in it.
+struct Document {
+    text: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+struct Server {
+    prons: PronounTrie,
+    documents: HashMap<String, Document>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "./dhall/package.dhall".to_string());
+    let pronouns: Vec<PronounSet> = serde_dhall::from_file(path).parse()?;
+
+    let mut server = Server {
+        prons: PronounTrie::build(pronouns),
+        documents: HashMap::new(),
+    };
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut output = io::stdout().lock();
+
+    while let Some(message) = read_message(&mut input)? {
+        let message = match message {
+            Ok(message) => message,
+            Err(why) => {
+                // There is no telling which request this was, so the id is null.
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": { "code": -32700, "message": format!("parse error: {why}") },
+                });
+                write_message(&mut output, &response)?;
+                continue;
+            }
+        };
+        let method = message["method"].as_str().unwrap_or_default();
+        let id = message.get("id").cloned();
+
+        if method == "exit" {
+            break;
+        }
+
+        let (result, notifications) = server.handle(method, &message["params"]);
+        for notification in notifications {
+            write_message(&mut output, &notification)?;
+        }
+
+        // Notifications don't get a response.
+        if let Some(id) = id {
+            let response = match result {
+                Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                None => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("unknown method {method}") },
+                }),
+            };
+            write_message(&mut output, &response)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Server {
+    /// Handle one message, returning the result if it was a request the server knows and any
+    /// notifications to send back.
+    fn handle(&mut self, method: &str, params: &Value) -> (Option<Value>, Vec<Value>) {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        match method {
+            "initialize" => (
+                Some(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "codeActionProvider": true,
+                    },
+                    "serverInfo": { "name": "xe_pronouns_lsp" },
+                })),
+                vec![],
+            ),
+            "shutdown" => (Some(Value::Null), vec![]),
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                (None, vec![self.update(uri, text.to_string())])
+            }
+            "textDocument/didChange" => {
+                // The server asks for full syncs, so the last change is the whole document.
+                let changes = params["contentChanges"].as_array();
+                match changes.and_then(|changes| changes.last()) {
+                    Some(change) => {
+                        let text = change["text"].as_str().unwrap_or_default();
+                        (None, vec![self.update(uri, text.to_string())])
+                    }
+                    None => (None, vec![]),
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                (None, vec![publish(&uri, vec![])])
+            }
+            "textDocument/codeAction" => (Some(self.code_actions(&uri, &params["range"])), vec![]),
+            // Notifications like "initialized" need nothing from us, and other requests get an
+            // error.
+            _ => (None, vec![]),
+        }
+    }
+
+    /// Store a new version of a document and lint it.
+    fn update(&mut self, uri: String, text: String) -> Value {
+        let (declared, body) = front_matter(&text);
+        let mut characters = Vec::new();
+        let mut lsp_diagnostics = Vec::new();
+
+        for declaration in declared {
+            match self.prons.resolve(&declaration.pronoun) {
+                Some(set) => characters.push(Character {
+                    name: declaration.name,
+                    set,
+                }),
+                None => lsp_diagnostics.push(json!({
+                    "range": range(&text, declaration.start, declaration.end),
+                    "severity": 1,
+                    "source": "pronouns",
+                    "message": format!("can't find {} in my database", declaration.pronoun),
+                })),
+            }
+        }
+
+        let mut diagnostics = self.prons.lint(&text[body..], &characters);
+        for diagnostic in diagnostics.iter_mut() {
+            diagnostic.start += body;
+            diagnostic.end += body;
+
+            lsp_diagnostics.push(json!({
+                "range": range(&text, diagnostic.start, diagnostic.end),
+                "severity": match diagnostic.severity {
+                    Severity::Warning => 2,
+                    Severity::Hint => 4,
+                },
+                "source": "pronouns",
+                "message": diagnostic.message,
+            }));
+        }
+
+        let notification = publish(&uri, lsp_diagnostics);
+        self.documents.insert(uri, Document { text, diagnostics });
+        notification
+    }
+
+    /// Offer to replace every mismatched form in a range with the declared one.
+    fn code_actions(&self, uri: &str, requested: &Value) -> Value {
+        let Some(document) = self.documents.get(uri) else {
+            return json!([]);
+        };
+        let text = &document.text;
+        let start = offset(text, &requested["start"]);
+        let end = offset(text, &requested["end"]);
+
+        let actions: Vec<Value> = document
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.start <= end && diagnostic.end >= start)
+            .map(|diagnostic| {
+                let range = range(text, diagnostic.start, diagnostic.end);
+                json!({
+                    "title": format!("Replace with \"{}\"", diagnostic.suggestion),
+                    "kind": "quickfix",
+                    "diagnostics": [{ "range": range, "message": diagnostic.message }],
+                    "edit": {
+                        "changes": {
+                            uri: [{ "range": range, "newText": diagnostic.suggestion }],
+                        },
+                    },
+                })
+            })
+            .collect();
+
+        json!(actions)
+    }
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Read the people declared in a document's front matter, returning them along with the byte
+/// offset the body starts at. Documents without front matter declare nobody.
+fn front_matter(text: &str) -> (Vec<Declared>, usize) {
+    let mut lines = text.split_inclusive('\n');
+    if lines.next().map(str::trim_end) != Some("---") {
+        return (vec![], 0);
+    }
+
+    let mut declared = Vec::new();
+    let mut at = text.find('\n').map_or(text.len(), |at| at + 1);
+    let mut in_pronouns = false;
+
+    for line in lines {
+        let start = at;
+        at += line.len();
+        let trimmed = line.trim();
+
+        if trimmed == "---" {
+            return (declared, at);
+        }
+
+        if !line.starts_with(char::is_whitespace) {
+            in_pronouns = trimmed == "pronouns:";
+            continue;
+        }
+
+        if let (true, Some((name, pronoun))) = (in_pronouns, trimmed.split_once(':')) {
+            let unquote = |s: &str| s.trim().trim_matches(['"', '\'']).to_string();
+            declared.push(Declared {
+                name: unquote(name),
+                pronoun: unquote(pronoun),
+                start,
+                end: start + line.trim_end().len(),
+            });
+        }
+    }
+
+    // The front matter was never closed, so it was just a horizontal rule.
+    (vec![], 0)
+}
+
+/// Turn a byte offset into an LSP position, which counts characters in UTF-16 code units.
+fn position(text: &str, offset: usize) -> Value {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |at| at + 1);
+
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+fn range(text: &str, start: usize, end: usize) -> Value {
+    json!({ "start": position(text, start), "end": position(text, end) })
+}
+
+/// Turn an LSP position back into a byte offset, clamping it to the text.
+fn offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or_default() as usize;
+    let character = position["character"].as_u64().unwrap_or_default() as usize;
+
+    let line_start: usize = text.split_inclusive('\n').take(line).map(str::len).sum();
+    let mut units = 0;
+
+    for (at, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return line_start + at;
+        }
+        units += c.len_utf16();
+    }
+
+    text.len()
+}
+
+/// Read the next message. The outer error is for a broken stream, which ends the session, and
+/// the inner one for a body that isn't JSON, which only that message is lost to.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Result<Value, serde_json::Error>>> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message without a Content-Length",
+        )
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    Ok(Some(serde_json::from_slice(&body)))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(body: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{body}", body.len())
+    }
+
+    #[test]
+    fn bad_json_only_loses_its_message() {
+        let input = frame("{oops") + &frame(r#"{"method":"exit"}"#);
+        let mut input = io::Cursor::new(input);
+
+        assert!(matches!(read_message(&mut input), Ok(Some(Err(_)))));
+        let message = read_message(&mut input).unwrap().unwrap().unwrap();
+        assert_eq!(message["method"], "exit");
+        assert!(read_message(&mut input).unwrap().is_none());
+    }
+
+    fn server() -> Server {
+        let sets: Vec<PronounSet> = serde_dhall::from_file("./dhall/package.dhall")
+            .parse()
+            .expect("dhall/package.dhall should parse");
+
+        Server {
+            prons: PronounTrie::build(sets),
+            documents: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn declarations() {
+        let text = "---\ntitle: Notes\npronouns:\n  Alex: xe/xem\n  \"Sam\": 'she/her'\n---\nHi.";
+        let (declared, body) = front_matter(text);
+
+        assert_eq!(&text[body..], "Hi.");
+        assert_eq!(declared.len(), 2);
+        assert_eq!(
+            (declared[0].name.as_str(), declared[0].pronoun.as_str()),
+            ("Alex", "xe/xem")
+        );
+        assert_eq!(&text[declared[0].start..declared[0].end], "  Alex: xe/xem");
+        assert_eq!(
+            (declared[1].name.as_str(), declared[1].pronoun.as_str()),
+            ("Sam", "she/her")
+        );
+    }
+
+    #[test]
+    fn no_front_matter() {
+        assert_eq!(front_matter("Alex left.").1, 0);
+
+        // Without a closing line the dashes were a horizontal rule.
+        let (declared, body) = front_matter("---\npronouns:\n  Alex: xe/xem\nAlex left.");
+        assert!(declared.is_empty());
+        assert_eq!(body, 0);
+    }
+
+    #[test]
+    fn positions_count_utf16() {
+        // "é" is two bytes but one UTF-16 unit, and "😀" is four bytes but two.
+        let text = "café\n😀 she\n";
+        let she = text.find("she").unwrap();
+
+        assert_eq!(position(text, she), json!({ "line": 1, "character": 3 }));
+        assert_eq!(position(text, 5), json!({ "line": 0, "character": 4 }));
+        assert_eq!(offset(text, &json!({ "line": 1, "character": 3 })), she);
+        assert_eq!(offset(text, &json!({ "line": 0, "character": 4 })), 5);
+
+        // Positions past the end of a line stop at it, and past the text at its end.
+        assert_eq!(offset(text, &json!({ "line": 0, "character": 99 })), 5);
+        assert_eq!(
+            offset(text, &json!({ "line": 9, "character": 0 })),
+            text.len()
+        );
+    }
+
+    #[test]
+    fn diagnostics_and_fixes() {
+        let mut server = server();
+        let uri = "file:///notes.md".to_string();
+        let text =
+            "---\npronouns:\n  Alex: xe/xem\n  Sam: shee/her\n---\nCafé 😀 Alex left. She waved.";
+
+        let notification = server.update(uri.clone(), text.to_string());
+        let diagnostics = notification["params"]["diagnostics"].as_array().unwrap();
+
+        // The typo isn't guessed at, so it's reported where it was declared.
+        assert_eq!(diagnostics[0]["severity"], 1);
+        assert_eq!(
+            diagnostics[0]["range"],
+            json!({
+                "start": { "line": 3, "character": 0 },
+                "end": { "line": 3, "character": 15 },
+            })
+        );
+
+        let she = json!({
+            "start": { "line": 5, "character": 19 },
+            "end": { "line": 5, "character": 22 },
+        });
+        assert_eq!(diagnostics[1]["range"], she);
+
+        let (actions, _) = server.handle(
+            "textDocument/codeAction",
+            &json!({ "textDocument": { "uri": uri }, "range": she }),
+        );
+        let actions = actions.unwrap();
+        assert_eq!(actions.as_array().unwrap().len(), 1);
+        assert_eq!(actions[0]["title"], "Replace with \"Xe\"");
+        assert_eq!(
+            actions[0]["edit"]["changes"][&uri],
+            json!([{ "range": she, "newText": "Xe" }])
+        );
+
+        let nowhere = json!({
+            "start": { "line": 5, "character": 0 },
+            "end": { "line": 5, "character": 4 },
+        });
+        let (actions, _) = server.handle(
+            "textDocument/codeAction",
+            &json!({ "textDocument": { "uri": uri }, "range": nowhere }),
+        );
+        assert_eq!(actions.unwrap(), json!([]));
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    examples::{introduce, Example},
+    template::Template,
+    PronounSet,
+};
+
+/// A themed pack of example sentences as it is written in dhall/examples.dhall.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct PackSource {
+    pub name: String,
+    pub description: String,
+    pub sentences: Vec<String>,
+}
+
+/// A themed pack of example sentences, parsed and ready to be filled in for any set.
+#[derive(Clone, Debug)]
+pub struct Pack {
+    pub name: String,
+    pub description: String,
+    sentences: Vec<Template>,
+}
+
+impl Pack {
+    pub fn new(source: PackSource) -> Result<Self, String> {
+        let sentences = source
+            .sentences
+            .iter()
+            .map(|sentence| Template::parse(sentence))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|why| format!("example pack {}: {why}", source.name))?;
+
+        Ok(Self {
+            name: source.name,
+            description: source.description,
+            sentences,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.sentences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sentences.is_empty()
+    }
+
+    /// Fill in every sentence for a set, introducing the person by name in the first one if a
+    /// name is given.
+    pub fn examples(&self, set: &PronounSet, name: Option<&str>) -> Vec<Example> {
+        let mut examples: Vec<Example> = self
+            .sentences
+            .iter()
+            .map(|sentence| Example {
+                parts: sentence.parts(set),
+            })
+            .collect();
+
+        if let Some(name) = name {
+            introduce(&mut examples, name);
+        }
+
+        examples
+    }
+}
+
+/// Every example pack that can be picked from, in the order they were defined.
+#[derive(Clone, Debug)]
+pub struct Corpus {
+    packs: Vec<Pack>,
+}
+
+impl Corpus {
+    pub fn new(sources: Vec<PackSource>) -> Result<Self, String> {
+        let packs = sources
+            .into_iter()
+            .map(Pack::new)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if packs.is_empty() {
+            return Err("the example corpus needs at least one pack".to_string());
+        }
+
+        Ok(Self { packs })
+    }
+
+    pub fn packs(&self) -> &[Pack] {
+        &self.packs
+    }
+
+    /// Get a pack by name, falling back to the first one if there is no such pack or no name.
+    pub fn get(&self, name: Option<&str>) -> &Pack {
+        name.and_then(|name| self.packs.iter().find(|pack| pack.name == name))
+            .unwrap_or(&self.packs[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corpus() -> Corpus {
+        let packs: Vec<PackSource> = serde_dhall::from_file("./dhall/examples.dhall")
+            .parse()
+            .expect("dhall/examples.dhall should parse");
+        Corpus::new(packs).expect("dhall/examples.dhall should be valid")
+    }
+
+    #[test]
+    fn packs_by_name() {
+        let corpus = corpus();
+        assert_eq!(corpus.get(None).name, corpus.packs()[0].name);
+        assert_eq!(corpus.get(Some("workplace")).name, "workplace");
+        assert_eq!(corpus.get(Some("nope")).name, corpus.packs()[0].name);
+    }
+
+    #[test]
+    fn every_pack_has_sentences() {
+        for pack in corpus().packs() {
+            assert!(!pack.is_empty(), "{}", pack.name);
+        }
+    }
+
+    #[test]
+    fn bad_packs() {
+        assert!(Corpus::new(vec![]).is_err());
+
+        let error = Pack::new(PackSource {
+            name: "broken".into(),
+            description: String::new(),
+            sentences: vec!["{nominitive} left.".into()],
+        })
+        .unwrap_err();
+        assert_eq!(
+            error,
+            "example pack broken: unknown placeholder {nominitive}"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use maud::{html, Markup, Render};
+use serde::Serialize;
+
+use super::{capitalize, Case, Number, Verb};
+
+/// The longest name that will be put into example sentences, in characters.
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// One piece of an example sentence.
+#[derive(Clone, Serialize, Debug, PartialEq, Eq)]
+#[serde(tag = "type", content = "text", rename_all = "snake_case")]
+pub enum Part {
+    /// Text that stays the same no matter which pronouns are used.
+    Text(String),
+    /// A pronoun in the given case, already capitalised if it starts the sentence.
+    Form(Case, String),
+    /// The name of the person the sentence is about.
+    Name(String),
+    /// A verb given as its bare infinitive, conjugated to agree with the set.
+    Verb(String, String),
+}
+
+/// An example sentence showing how a set is used.
+#[derive(Clone, Serialize, Default, Debug, PartialEq, Eq)]
+pub struct Example {
+    pub parts: Vec<Part>,
+}
+
+impl Example {
+    /// Introduce someone by name: the first nominative in the sentence is replaced with their
+    /// name, and the verb it is the subject of is conjugated for one person so that "they go"
+    /// becomes "Alex goes". Returns false if there was no nominative to replace.
+    pub fn introduce(&mut self, name: &str) -> bool {
+        let Some(at) = self
+            .parts
+            .iter()
+            .position(|part| matches!(part, Part::Form(Case::Nominative, _)))
+        else {
+            return false;
+        };
+
+        self.parts[at] = Part::Name(name.to_string());
+
+        for part in &mut self.parts[at + 1..] {
+            match part {
+                Part::Verb(verb, text) => {
+                    let singular = Verb(verb).present(Number::Singular);
+                    *text = if text.starts_with(char::is_uppercase) {
+                        capitalize(&singular)
+                    } else {
+                        singular
+                    };
+                    break;
+                }
+                Part::Form(..) | Part::Name(_) => break,
+                Part::Text(_) => {}
+            }
+        }
+
+        true
+    }
+}
+
+impl fmt::Display for Example {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                Part::Text(text) | Part::Form(_, text) | Part::Name(text) | Part::Verb(_, text) => {
+                    f.write_str(text)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Render for Example {
+    fn render(&self) -> Markup {
+        html! {
+            @for part in &self.parts {
+                @match part {
+                    Part::Text(text) | Part::Verb(_, text) => (text),
+                    Part::Form(_, form) => em { (form) },
+                    Part::Name(name) => strong { (name) },
+                }
+            }
+        }
+    }
+}
+
+/// Make a name from user input safe to show: control characters are dropped, surrounding
+/// whitespace is trimmed and it is cut down to [MAX_NAME_LENGTH] characters. Returns None if
+/// nothing is left. HTML escaping is left to whatever renders the name.
+pub fn clean_name(name: &str) -> Option<String> {
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .chars()
+        .take(MAX_NAME_LENGTH)
+        .collect();
+
+    let name = name.trim_end().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Put a name into the first sentence of a story that has somewhere to put it.
+pub(crate) fn introduce(examples: &mut [Example], name: &str) {
+    for example in examples.iter_mut() {
+        if example.introduce(name) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{template::Template, PronounSet};
+
+    fn example(source: &str, set: &PronounSet) -> Example {
+        Example {
+            parts: Template::parse(source).unwrap().parts(set),
+        }
+    }
+
+    #[test]
+    fn introducing_conjugates_for_one_person() {
+        let trie = crate::test_trie();
+        let they = &trie.guess(&mut vec![Some("they".into()), Some("them".into())])[0];
+
+        for (source, introduced) in [
+            (
+                "{Nominative} {verb:be} really good at it.",
+                "Alex is really good at it.",
+            ),
+            (
+                "Every weekend {nominative} {verb:go} back.",
+                "Every weekend Alex goes back.",
+            ),
+            (
+                "{Nominative} {verb:carry} a frisbee.",
+                "Alex carries a frisbee.",
+            ),
+            ("{Nominative} went to the park.", "Alex went to the park."),
+        ] {
+            let mut example = example(source, they);
+            assert!(example.introduce("Alex"));
+            assert_eq!(example.to_string(), introduced);
+        }
+    }
+
+    #[test]
+    fn introducing_leaves_other_subjects_alone() {
+        let trie = crate::test_trie();
+        let they = &trie.guess(&mut vec![Some("they".into()), Some("them".into())])[0];
+
+        let mut example = example("{Nom} said {nom} {verb:be} fine.", they);
+        assert!(example.introduce("Alex"));
+        assert_eq!(example.to_string(), "Alex said they are fine.");
+    }
+
+    #[test]
+    fn introducing_without_a_nominative() {
+        let trie = crate::test_trie();
+        let she = &trie.guess(&mut vec![Some("she".into())])[0];
+
+        let mut example = example("I went with {accusative}.", she);
+        assert!(!example.introduce("Alex"));
+        assert_eq!(example.to_string(), "I went with her.");
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{normalize, trie::key_of, Case, PronounSet};
+
+/// A set that has a form that was looked up, and the case it has it in.
+#[derive(Clone, Serialize, Debug)]
+pub struct FormMatch {
+    pub set: PronounSet,
+    pub case: Case,
+}
+
+/// Indexes from the forms in each case to the sets that have them, so that looking up a form
+/// like "xemself" doesn't have to walk the whole trie.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct FormIndex {
+    /// One map per case, in the order of [Case::ALL], keyed by the normalised form. Each list is
+    /// sorted by the set's forms, the same order the trie keeps them in.
+    cases: [HashMap<String, Vec<PronounSet>>; 5],
+}
+
+impl FormIndex {
+    pub(crate) fn build<'a>(sets: impl IntoIterator<Item = &'a PronounSet>) -> Self {
+        let mut index = Self::default();
+        for set in sets {
+            index.insert(set);
+        }
+        index
+    }
+
+    /// Add a set to every case's index, replacing any set with the same forms.
+    pub(crate) fn insert(&mut self, set: &PronounSet) {
+        let key = key_of(set);
+
+        for (case, form) in Case::ALL.iter().zip(&key) {
+            let sets = self.cases[*case as usize].entry(form.clone()).or_default();
+            sets.retain(|other| key_of(other) != key);
+
+            let at = sets.partition_point(|other| key_of(other) < key);
+            sets.insert(at, set.clone());
+        }
+    }
+
+    /// Every set that has this form in this case.
+    pub(crate) fn get(&self, case: Case, form: &str) -> &[PronounSet] {
+        self.cases[case as usize]
+            .get(&normalize(form))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{trie::expand_wildcards, Case, PronounSet, PronounTrie};
+
+/// A [PronounSet] that was filled in from partial input instead of being found in the database.
+#[derive(Clone, Serialize, Debug)]
+pub struct Inference {
+    pub set: PronounSet,
+    /// How sure the guess is, between 0 and 1. This is the average share of the database that
+    /// agreed with each missing form.
+    pub confidence: f64,
+}
+
+/// A way to derive one case's form from another one: take the source form, remove `strip` from
+/// the end of it and add `append`. "xem" becomes "xemself" with `strip` empty and `append` set to
+/// "self".
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Rule {
+    from: Case,
+    to: Case,
+    strip: String,
+    append: String,
+}
+
+impl Rule {
+    fn learn(set: &PronounSet, from: Case, to: Case) -> Self {
+        let source = set.form(from);
+        let target = set.form(to);
+
+        let common = source
+            .char_indices()
+            .zip(target.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8());
+
+        Self {
+            from,
+            to,
+            strip: source[common..].to_string(),
+            append: target[common..].to_string(),
+        }
+    }
+
+    fn apply(&self, form: &str) -> Option<String> {
+        form.strip_suffix(self.strip.as_str())
+            .map(|stem| format!("{stem}{}", self.append))
+    }
+}
+
+/// How many sets in the trie follow each [Rule]. The trie keeps this up to date as sets are added,
+/// so inferring a set doesn't have to look at every one of them again.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct Rules {
+    counts: HashMap<Rule, usize>,
+}
+
+impl Rules {
+    /// Count the rules a set follows.
+    pub(crate) fn learn(&mut self, set: &PronounSet) {
+        for rule in Self::followed_by(set) {
+            *self.counts.entry(rule).or_default() += 1;
+        }
+    }
+
+    /// Stop counting the rules a set follows, for when it is replaced.
+    pub(crate) fn forget(&mut self, set: &PronounSet) {
+        for rule in Self::followed_by(set) {
+            if let Some(count) = self.counts.get_mut(&rule) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&rule);
+                }
+            }
+        }
+    }
+
+    fn followed_by(set: &PronounSet) -> impl Iterator<Item = Rule> + '_ {
+        Case::ALL.into_iter().flat_map(move |from| {
+            Case::ALL
+                .into_iter()
+                .filter(move |to| *to != from)
+                .map(move |to| Rule::learn(set, from, to))
+        })
+    }
+}
+
+impl PronounTrie {
+    /// Propose a complete set from one to four known forms, given positionally like the key to
+    /// [PronounTrie::guess]. The missing forms are derived with suffix rules learned from every set
+    /// in the trie, so `xe/xem` becomes `xe/xem/xyr/xyrs/xemself` if that is what the database
+    /// mostly does. Returns None if there is nothing to go on, there are more than five forms, or
+    /// a form can't be derived.
+    pub fn infer(&self, key: &[Option<String>]) -> Option<Inference> {
+        if key.len() > Case::ALL.len() {
+            return None;
+        }
+
+        let rules = self.rules()?;
+        let mut key = key.to_vec();
+        expand_wildcards(&mut key);
+
+        let known: Vec<(Case, &str)> = Case::ALL
+            .iter()
+            .zip(key.iter())
+            .filter_map(|(case, form)| form.as_deref().map(|form| (*case, form)))
+            .collect();
+
+        if known.is_empty() {
+            return None;
+        }
+
+        let mut result = PronounSet::default();
+        let mut confidences = Vec::new();
+
+        for case in Case::ALL {
+            if let Some((_, form)) = known.iter().find(|(from, _)| *from == case) {
+                *result.form_mut(case) = form.to_string();
+                continue;
+            }
+
+            let candidates: Vec<(&Rule, usize, String)> = rules
+                .counts
+                .iter()
+                .filter(|(rule, _)| rule.to == case)
+                .filter_map(|(rule, count)| {
+                    let (_, form) = known.iter().find(|(from, _)| *from == rule.from)?;
+                    Some((rule, *count, rule.apply(form)?))
+                })
+                .collect();
+
+            let support: usize = candidates.iter().map(|(_, count, _)| count).sum();
+
+            // Prefer the rule most sets follow, then the most specific one, then the alphabetically
+            // first result so the answer doesn't depend on hash order.
+            let (_, count, form) = candidates.into_iter().max_by(|a, b| {
+                a.1.cmp(&b.1)
+                    .then(a.0.strip.len().cmp(&b.0.strip.len()))
+                    .then(b.2.cmp(&a.2))
+            })?;
+
+            *result.form_mut(case) = form;
+            confidences.push(count as f64 / support as f64);
+        }
+
+        // A rule that strips a whole form leaves nothing behind, and an empty form isn't a guess
+        // worth showing.
+        if Case::ALL.iter().any(|&case| result.form(case).is_empty()) {
+            return None;
+        }
+
+        result.infer_number();
+
+        let confidence = if confidences.is_empty() {
+            1.0
+        } else {
+            confidences.iter().sum::<f64>() / confidences.len() as f64
+        };
+
+        Some(Inference {
+            set: result,
+            confidence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_trie;
+
+    fn key(forms: &[&str]) -> Vec<Option<String>> {
+        forms.iter().map(|form| Some(form.to_string())).collect()
+    }
+
+    #[test]
+    fn known_sets_round_trip() {
+        let prons = test_trie();
+
+        // Sets whose other forms are built from the accusative, like ze/zir/zir/zirs/zirself,
+        // are what the rules learn, so giving the first two forms gets the rest back.
+        let regular: Vec<PronounSet> = prons
+            .gather()
+            .into_iter()
+            .filter(|set| {
+                set.determiner == set.accusative
+                    && set.possessive == format!("{}s", set.accusative)
+                    && set.reflexive == format!("{}self", set.accusative)
+            })
+            .collect();
+        assert!(regular.len() >= 10, "only {} regular sets", regular.len());
+
+        for set in regular {
+            let inferred = prons
+                .infer(&key(&[&set.nominative, &set.accusative]))
+                .unwrap_or_else(|| panic!("can't infer {}", set.url()));
+            assert_eq!(inferred.set.url(), set.url());
+        }
+    }
+
+    #[test]
+    fn three_forms_round_trip() {
+        let prons = test_trie();
+
+        for url in [
+            "/they/them/their/theirs/themself",
+            "/xe/xem/xyr/xyrs/xemself",
+            "/e/em/eir/eirs/emself",
+        ] {
+            let forms: Vec<&str> = url[1..].split('/').collect();
+            let inferred = prons.infer(&key(&forms[..3])).unwrap();
+            assert_eq!(inferred.set.url(), url);
+        }
+    }
+
+    #[test]
+    fn missing_forms_are_derived() {
+        let inferred = test_trie().infer(&key(&["qe", "qem"])).unwrap();
+
+        assert_eq!(inferred.set.url(), "/qe/qem/qem/qems/qemself");
+        assert!(inferred.confidence > 0.0 && inferred.confidence < 1.0);
+    }
+
+    #[test]
+    fn more_than_five_forms() {
+        let prons = test_trie();
+
+        assert!(prons
+            .infer(&key(&["xe", "xem", "xyr", "xyrs", "xemself", "extra"]))
+            .is_none());
+    }
+
+    #[test]
+    fn nothing_to_go_on() {
+        assert!(test_trie().infer(&[]).is_none());
+        assert!(test_trie().infer(&[None, None]).is_none());
+    }
+
+    #[test]
+    fn empty_forms() {
+        // The only rules strip "yz" off the end, which leaves nothing of "yz".
+        let prons = PronounTrie::build(vec![PronounSet::new(
+            "xyz".to_string(),
+            "x".to_string(),
+            "x".to_string(),
+            "x".to_string(),
+            "x".to_string(),
+        )]);
+
+        assert!(prons.infer(&key(&["yz"])).is_none());
+    }
+
+    #[test]
+    fn inserted_sets_are_learned() {
+        let mut prons = PronounTrie::build(vec![PronounSet::new(
+            "fae".to_string(),
+            "faer".to_string(),
+            "faer".to_string(),
+            "faers".to_string(),
+            "faerself".to_string(),
+        )]);
+        assert_eq!(
+            prons.infer(&key(&["e", "em"])).unwrap().set.url(),
+            "/e/em/em/ems/emself"
+        );
+
+        for (nominative, accusative, determiner) in [
+            ("ey", "em", "eir"),
+            ("xey", "xem", "xeir"),
+            ("they", "them", "their"),
+        ] {
+            prons.insert(PronounSet::new(
+                nominative.to_string(),
+                accusative.to_string(),
+                determiner.to_string(),
+                format!("{determiner}s"),
+                format!("{accusative}self"),
+            ));
+        }
+
+        assert_eq!(
+            prons.infer(&key(&["e", "em"])).unwrap().set.url(),
+            "/e/em/eir/eirs/emself"
+        );
+    }
+}
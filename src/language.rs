@@ -0,0 +1,516 @@
+use std::collections::HashMap;
+
+use maud::{html, Markup};
+use serde::{Deserialize, Serialize};
+use xe_pronouns_syntax::{tokenize, Token};
+
+use super::{capitalize, normalize, PronounSet, PronounTrie, TemplateError};
+
+/// A grammatical case (or other slot, like an adjective ending) that sets in a language have a
+/// form for.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct LanguageCase {
+    /// What the case is called in example sentence placeholders, like "dativ".
+    pub name: String,
+    /// What the case is called on pages, like "Dativ".
+    pub label: String,
+}
+
+/// A language as it is written in dhall/languages/*.dhall.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct LanguageSource {
+    /// The code used in URLs, like "de".
+    pub code: String,
+    /// The name of the language in that language, like "Deutsch".
+    pub name: String,
+    pub cases: Vec<LanguageCase>,
+    /// Every set, with one form per case in the same order as the cases.
+    pub sets: Vec<Vec<String>>,
+    /// Example sentences with a `{case}` placeholder for each form. Capitalise the case name
+    /// (`{Nominativ}`) to capitalise the form; forms that start a sentence are capitalised either
+    /// way. Write `{{` and `}}` for literal braces.
+    pub sentences: Vec<String>,
+}
+
+/// A pronoun set in a language other than English.
+#[derive(Clone, Serialize, Debug, PartialEq, Eq)]
+pub struct LanguageSet {
+    pub language: String,
+    /// One form per case in the language's inventory.
+    pub forms: Vec<String>,
+}
+
+impl LanguageSet {
+    pub fn url(&self) -> String {
+        format!("/{}/{}", self.language, self.forms.join("/"))
+    }
+
+    pub fn title(&self) -> String {
+        self.forms
+            .iter()
+            .take(2)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// A set in another language that people who use an English set often pick, as it is written in
+/// dhall/equivalents.dhall.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct EquivalentSource {
+    /// The title of the English set, like "they/them".
+    pub pronoun: String,
+    /// The code of the other language, like "sv".
+    pub language: String,
+    /// Enough forms to find the set in the other language.
+    pub forms: Vec<String>,
+    /// How the set is used in that language.
+    pub note: String,
+}
+
+/// A set in another language to suggest alongside an English one.
+#[derive(Clone, Serialize, Debug)]
+pub struct Equivalent {
+    /// The name of the language, like "Svenska".
+    pub name: String,
+    pub set: LanguageSet,
+    pub note: String,
+}
+
+/// One piece of a parsed example sentence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    /// The form for the case at this index, and whether to capitalise it.
+    Form(usize, bool),
+}
+
+/// A language with its own case inventory, sets and example sentences.
+#[derive(Clone, Debug)]
+pub struct Language {
+    pub code: String,
+    pub name: String,
+    pub cases: Vec<LanguageCase>,
+    sets: Vec<LanguageSet>,
+    sentences: Vec<Vec<Piece>>,
+}
+
+impl Language {
+    pub fn new(source: LanguageSource) -> Result<Self, String> {
+        let code = source.code;
+
+        if let Some(set) = source
+            .sets
+            .iter()
+            .find(|set| set.len() != source.cases.len())
+        {
+            return Err(format!(
+                "language {code}: {} has {} forms but there are {} cases",
+                set.join("/"),
+                set.len(),
+                source.cases.len()
+            ));
+        }
+
+        let sentences = source
+            .sentences
+            .iter()
+            .map(|sentence| parse(sentence, &source.cases))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|why| format!("language {code}: {why}"))?;
+
+        let sets = source
+            .sets
+            .into_iter()
+            .map(|forms| LanguageSet {
+                language: code.clone(),
+                forms,
+            })
+            .collect();
+
+        Ok(Self {
+            code,
+            name: source.name,
+            cases: source.cases,
+            sets,
+            sentences,
+        })
+    }
+
+    pub fn sets(&self) -> &[LanguageSet] {
+        &self.sets
+    }
+
+    /// Find every set whose forms start with the given ones, in the same way as
+    /// [PronounTrie::guess](crate::PronounTrie::guess). None matches any form, and forms are
+    /// compared after [normalize].
+    pub fn guess(&self, key: &[Option<String>]) -> Vec<&LanguageSet> {
+        if key.is_empty() || key.len() > self.cases.len() {
+            return vec![];
+        }
+
+        self.sets
+            .iter()
+            .filter(|set| {
+                key.iter().zip(&set.forms).all(|(wanted, form)| {
+                    wanted
+                        .iter()
+                        .all(|wanted| normalize(wanted) == normalize(form))
+                })
+            })
+            .collect()
+    }
+
+    /// Fill in every example sentence for a set.
+    pub fn examples(&self, set: &LanguageSet) -> Vec<Markup> {
+        self.sentences
+            .iter()
+            .map(|pieces| {
+                html! {
+                    @for piece in pieces {
+                        @match piece {
+                            Piece::Text(text) => (text),
+                            Piece::Form(index, true) => em { (capitalize(&set.forms[*index])) },
+                            Piece::Form(index, false) => em { (set.forms[*index]) },
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Render the page for a set in this language.
+    pub fn page(&self, set: &LanguageSet) -> Markup {
+        html! {
+            table {
+                @for (case, form) in self.cases.iter().zip(&set.forms) {
+                    tr {
+                        th { (case.label) }
+                        td { (form) }
+                    }
+                }
+            }
+            @if !self.sentences.is_empty() {
+                p {"Here are some example sentences with these pronouns:"}
+                ul {
+                    @for example in self.examples(set) {
+                        li { (example) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parse an example sentence with the same tokenizer as [Template](crate::Template), checking
+/// that every placeholder names one of the cases.
+fn parse(sentence: &str, cases: &[LanguageCase]) -> Result<Vec<Piece>, String> {
+    let fail = |why: TemplateError| format!("{why} in {sentence:?}");
+
+    tokenize(sentence)
+        .map_err(fail)?
+        .into_iter()
+        .map(|token| {
+            let capitalised = token.capitalised();
+            match token {
+                Token::Text(text) => Ok(Piece::Text(text)),
+                Token::Placeholder { name, .. } => cases
+                    .iter()
+                    .position(|case| case.name == name.to_lowercase())
+                    .map(|index| Piece::Form(index, capitalised))
+                    .ok_or_else(|| fail(TemplateError::UnknownPlaceholder(name.to_string()))),
+            }
+        })
+        .collect()
+}
+
+/// Every language other than English that has sets, in the order they were defined.
+#[derive(Clone, Default, Debug)]
+pub struct Languages {
+    languages: Vec<Language>,
+    /// Equivalents keyed by the title of the English set.
+    equivalents: HashMap<String, Vec<Equivalent>>,
+}
+
+impl Languages {
+    /// Load every language, then check that every equivalent is for the title of an English set
+    /// in `english` and points at exactly one set in its language. Equivalents are looked up by
+    /// title, so one with a misspelled title would otherwise never be shown.
+    pub fn new(
+        sources: Vec<LanguageSource>,
+        equivalents: Vec<EquivalentSource>,
+        english: &PronounTrie,
+    ) -> Result<Self, String> {
+        let mut result = Self {
+            languages: sources
+                .into_iter()
+                .map(Language::new)
+                .collect::<Result<Vec<_>, _>>()?,
+            equivalents: HashMap::new(),
+        };
+
+        let titles: Vec<String> = english.gather().iter().map(PronounSet::title).collect();
+
+        for equivalent in equivalents {
+            if !titles.contains(&equivalent.pronoun) {
+                return Err(format!(
+                    "equivalent for {}: there is no English set with that title",
+                    equivalent.pronoun
+                ));
+            }
+
+            let language = result.get(&equivalent.language).ok_or_else(|| {
+                format!(
+                    "equivalent for {}: there is no language {}",
+                    equivalent.pronoun, equivalent.language
+                )
+            })?;
+
+            let key: Vec<Option<String>> = equivalent.forms.iter().cloned().map(Some).collect();
+            let set = match language.guess(&key)[..] {
+                [set] => set.clone(),
+                ref found => {
+                    return Err(format!(
+                        "equivalent for {}: {}/{} matches {} sets instead of one",
+                        equivalent.pronoun,
+                        equivalent.language,
+                        equivalent.forms.join("/"),
+                        found.len()
+                    ))
+                }
+            };
+
+            let name = language.name.clone();
+            result
+                .equivalents
+                .entry(equivalent.pronoun)
+                .or_default()
+                .push(Equivalent {
+                    name,
+                    set,
+                    note: equivalent.note,
+                });
+        }
+
+        Ok(result)
+    }
+
+    pub fn all(&self) -> &[Language] {
+        &self.languages
+    }
+
+    /// Get a language by the code used in URLs, like "de". Codes are compared after [normalize],
+    /// so "DE" finds it too.
+    pub fn get(&self, code: &str) -> Option<&Language> {
+        let code = normalize(code);
+        self.languages
+            .iter()
+            .find(|language| normalize(&language.code) == code)
+    }
+
+    /// Sets in other languages to suggest to someone who uses this set, in the order they were
+    /// defined.
+    pub fn equivalents(&self, set: &PronounSet) -> &[Equivalent] {
+        self.equivalents
+            .get(&set.title())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The "In other languages" section of a set's page, or nothing if there are no equivalents.
+    pub fn equivalents_section(&self, set: &PronounSet) -> Markup {
+        let equivalents = self.equivalents(set);
+
+        html! {
+            @if !equivalents.is_empty() {
+                h2 { "In other languages" }
+                ul {
+                    @for equivalent in equivalents {
+                        li {
+                            (equivalent.name)
+                            ": "
+                            a href=(equivalent.set.url()) { (equivalent.set.title()) }
+                            ". "
+                            (equivalent.note)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cases() -> Vec<LanguageCase> {
+        ["nominativ", "dativ"]
+            .into_iter()
+            .map(|name| LanguageCase {
+                name: name.to_string(),
+                label: capitalize(name),
+            })
+            .collect()
+    }
+
+    fn german() -> LanguageSource {
+        LanguageSource {
+            code: "de".to_string(),
+            name: "Deutsch".to_string(),
+            cases: cases(),
+            sets: [
+                ["sie", "ihr"],
+                ["xier", "xiem"],
+                ["sier", "siem"],
+                ["sier", "sierm"],
+            ]
+            .into_iter()
+            .map(|forms| forms.map(String::from).to_vec())
+            .collect(),
+            sentences: vec!["{Nominativ} geht mit {dativ}.".to_string()],
+        }
+    }
+
+    fn titles(sets: Vec<&LanguageSet>) -> Vec<String> {
+        sets.into_iter().map(LanguageSet::title).collect()
+    }
+
+    #[test]
+    fn forms_must_match_the_cases() {
+        let mut source = german();
+        source.sets.push(vec!["xier".to_string()]);
+
+        assert_eq!(
+            Language::new(source).unwrap_err(),
+            "language de: xier has 1 forms but there are 2 cases"
+        );
+    }
+
+    #[test]
+    fn guessing() {
+        let language = Language::new(german()).unwrap();
+        let key = |forms: &[Option<&str>]| -> Vec<Option<String>> {
+            forms.iter().map(|form| form.map(String::from)).collect()
+        };
+
+        assert_eq!(titles(language.guess(&key(&[Some("XIER")]))), ["xier/xiem"]);
+        assert_eq!(titles(language.guess(&key(&[Some("ｓｉｅ")]))), ["sie/ihr"]);
+        assert_eq!(
+            titles(language.guess(&key(&[Some("sier")]))),
+            ["sier/siem", "sier/sierm"]
+        );
+        assert_eq!(
+            titles(language.guess(&key(&[None, Some("Sierm")]))),
+            ["sier/sierm"]
+        );
+        assert!(language.guess(&key(&[Some("er")])).is_empty());
+        assert!(language.guess(&key(&[])).is_empty());
+        assert!(language
+            .guess(&key(&[Some("xier"), Some("xiem"), Some("xies")]))
+            .is_empty());
+    }
+
+    #[test]
+    fn codes_are_normalized() {
+        let languages = Languages::new(vec![german()], vec![], &crate::test_trie()).unwrap();
+
+        assert_eq!(languages.get("de").unwrap().name, "Deutsch");
+        assert_eq!(languages.get("DE").unwrap().name, "Deutsch");
+        assert!(languages.get("sv").is_none());
+    }
+
+    fn equivalent(pronoun: &str, language: &str, forms: &[&str]) -> EquivalentSource {
+        EquivalentSource {
+            pronoun: pronoun.to_string(),
+            language: language.to_string(),
+            forms: forms.iter().map(|form| form.to_string()).collect(),
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn bad_equivalents() {
+        let english = crate::test_trie();
+        let check = |equivalent| Languages::new(vec![german()], vec![equivalent], &english);
+
+        assert!(check(equivalent("they/them", "de", &["xier"])).is_ok());
+        assert_eq!(
+            check(equivalent("They/Them", "de", &["xier"])).unwrap_err(),
+            "equivalent for They/Them: there is no English set with that title"
+        );
+        assert_eq!(
+            check(equivalent("they/them", "sv", &["hen"])).unwrap_err(),
+            "equivalent for they/them: there is no language sv"
+        );
+        assert_eq!(
+            check(equivalent("they/them", "de", &["sier"])).unwrap_err(),
+            "equivalent for they/them: de/sier matches 2 sets instead of one"
+        );
+        assert_eq!(
+            check(equivalent("they/them", "de", &["dey"])).unwrap_err(),
+            "equivalent for they/them: de/dey matches 0 sets instead of one"
+        );
+    }
+
+    #[test]
+    fn shipped_equivalents() {
+        let sources: Vec<LanguageSource> = serde_dhall::from_file("./dhall/languages.dhall")
+            .parse()
+            .expect("dhall/languages.dhall should parse");
+        let equivalents: Vec<EquivalentSource> =
+            serde_dhall::from_file("./dhall/equivalents.dhall")
+                .parse()
+                .expect("dhall/equivalents.dhall should parse");
+        let english = crate::test_trie();
+        let languages = Languages::new(sources, equivalents, &english).unwrap();
+
+        let they = english.resolve("they/them").unwrap();
+        let urls: Vec<String> = languages
+            .equivalents(&they)
+            .iter()
+            .map(|equivalent| equivalent.set.url())
+            .collect();
+        assert!(
+            urls.contains(&"/de/xier/xien/xiem/xies".to_string()),
+            "{urls:?}"
+        );
+        assert!(urls.contains(&"/sv/hen/hen/hens".to_string()), "{urls:?}");
+
+        let section = languages.equivalents_section(&they).into_string();
+        assert!(section.contains("<h2>In other languages</h2>"));
+        assert!(section.contains(r#"<a href="/de/xier/xien/xiem/xies">xier/xien</a>"#));
+
+        let kit = english.resolve("kit/kit").unwrap();
+        assert!(languages.equivalents(&kit).is_empty());
+        assert_eq!(languages.equivalents_section(&kit).into_string(), "");
+    }
+
+    #[test]
+    fn sentences() {
+        assert_eq!(
+            parse("{Nominativ} geht mit {dativ}. {dativ}! {{x}}", &cases()).unwrap(),
+            vec![
+                Piece::Form(0, true),
+                Piece::Text(" geht mit ".into()),
+                Piece::Form(1, false),
+                Piece::Text(". ".into()),
+                Piece::Form(1, true),
+                Piece::Text("! {x}".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_placeholders() {
+        assert_eq!(
+            parse("mit {akkusativ}", &cases()),
+            Err(r#"unknown placeholder {akkusativ} in "mit {akkusativ}""#.to_string())
+        );
+        assert_eq!(
+            parse("mit {dativ", &cases()),
+            Err(r#"unclosed placeholder at byte 4 in "mit {dativ""#.to_string())
+        );
+    }
+}
@@ -1,10 +1,49 @@
-use heck::ToTitleCase;
 use maud::{html, Markup, Render};
 use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
+mod analyze;
+mod corpus;
+mod examples;
+mod forms;
+mod infer;
+mod language;
+mod lint;
+mod neutralize;
+mod nounself;
+mod parse;
+mod preference;
+mod profile;
+mod rewrite;
+mod template;
+mod terms;
 mod trie;
+mod verb;
 
-pub use trie::PronounTrie;
+pub use analyze::{Candidate, Evidence};
+pub use corpus::{Corpus, Pack, PackSource};
+pub use examples::{clean_name, Example, Part, MAX_NAME_LENGTH};
+pub use forms::FormMatch;
+pub use infer::Inference;
+pub use language::{
+    Equivalent, EquivalentSource, Language, LanguageCase, LanguageSet, LanguageSource, Languages,
+};
+pub use lint::{Character, Diagnostic, Severity};
+pub use neutralize::Suggestion;
+pub use parse::Declaration;
+pub use preference::Preference;
+pub use profile::{Policy, ProfileEntry, PronounProfile};
+pub use rewrite::{Rewrite, Substitution};
+pub use template::{Template, TemplateError};
+pub use terms::{TermPreferences, Terms};
+
+pub use trie::{Completion, FuzzyMatch, PronounTrie};
+pub use verb::{Contractions, Number, Verb};
+
+/// Check pronoun templates at compile time, see [Template] for the syntax.
+#[cfg(feature = "macros")]
+pub use xe_pronouns_macros::pronoun_format;
 
 #[derive(Clone, Deserialize, Serialize, Default, Debug)]
 pub struct PronounSet {
@@ -13,12 +52,92 @@ pub struct PronounSet {
     pub determiner: String,
     pub possessive: String,
     pub reflexive: String,
+    /// Whether the set refers to a single person. This is what picks "themself" over
+    /// "themselves", and has nothing to do with verb agreement.
     #[serde(default)]
     pub singular: bool,
+    /// The number verbs take when this set is the subject. Singular "they" is still "they are".
+    #[serde(default)]
+    pub agreement: Number,
+}
+
+/// One of the five grammatical cases a [PronounSet] has a form for.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Case {
+    Nominative,
+    Accusative,
+    Determiner,
+    Possessive,
+    Reflexive,
+}
+
+impl Case {
+    /// Every case, in the order they appear in URLs.
+    pub const ALL: [Case; 5] = [
+        Case::Nominative,
+        Case::Accusative,
+        Case::Determiner,
+        Case::Possessive,
+        Case::Reflexive,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Case::Nominative => "nominative",
+            Case::Accusative => "accusative",
+            Case::Determiner => "determiner",
+            Case::Possessive => "possessive",
+            Case::Reflexive => "reflexive",
+        }
+    }
+}
+
+impl From<xe_pronouns_syntax::Case> for Case {
+    fn from(case: xe_pronouns_syntax::Case) -> Self {
+        match case {
+            xe_pronouns_syntax::Case::Nominative => Case::Nominative,
+            xe_pronouns_syntax::Case::Accusative => Case::Accusative,
+            xe_pronouns_syntax::Case::Determiner => Case::Determiner,
+            xe_pronouns_syntax::Case::Possessive => Case::Possessive,
+            xe_pronouns_syntax::Case::Reflexive => Case::Reflexive,
+        }
+    }
 }
 
+/// Everything on a set's page but the example sentences, which need a [Pack], see
+/// [PronounSet::page].
 impl Render for PronounSet {
     fn render(&self) -> Markup {
+        html! {
+            (self.forms_table())
+            p {"These are the contracted forms:"}
+            (self.contractions_table())
+            (self.inflection_note())
+        }
+    }
+}
+
+impl PronounSet {
+    /// Render the page for this set with example sentences from the given pack, introducing the
+    /// person by name in them if one is given.
+    pub fn page(&self, pack: &Pack, name: Option<&str>) -> Markup {
+        html! {
+            (self.forms_table())
+            p {"Here are some example sentences with these pronouns:"}
+            ul {
+                @for example in pack.examples(self, name) {
+                    li { (example) }
+                }
+            }
+            p {"These are the contracted forms:"}
+            (self.contractions_table())
+            (self.inflection_note())
+        }
+    }
+
+    /// A table listing every form in this set.
+    pub fn forms_table(&self) -> Markup {
         html! {
             table {
                 tr {
@@ -42,39 +161,106 @@ impl Render for PronounSet {
                     td {(self.reflexive)}
                 }
             }
-            p {"Here are some example sentences with these pronouns:"}
-            ul {
-                li { em{(self.nominative.to_title_case())} " went to the park." }
-                li { "I went with " i{(self.accusative)} "." }
-                li { em{(self.nominative.to_title_case())} " brought " em{(self.determiner)} " frisbee." }
-                li { "At least I think it was " em{(self.possessive)} "." }
-                li {
-                    em{(self.nominative.to_title_case())}
-                    " threw the frisbee "
-                    @if self.singular {
-                        "to"
-                    } @else {
-                        "between"
+        }
+    }
+
+    /// A table listing the contracted forms of the nominative.
+    pub fn contractions_table(&self) -> Markup {
+        let rows = [
+            ("be", self.conjugate("be")),
+            ("have", self.conjugate("have")),
+            ("will", "will".to_string()),
+            ("would", "would / had".to_string()),
+        ];
+
+        html! {
+            table {
+                @for (verb, label) in rows {
+                    @if let Some(contraction) = self.contraction(verb) {
+                        tr {
+                            th { (self.nominative) " " (label) }
+                            td { (contraction) }
+                        }
                     }
-                    " "
-                    em{(self.reflexive)}
-                    "."
                 }
             }
+        }
+    }
+
+    /// A note on how verbs should agree with this set.
+    pub fn inflection_note(&self) -> Markup {
+        html! {
             p {
                 "This pronoun should be inflected as a "
-                @if self.singular {
-                    "singular"
-                } @else {
+                @if self.plural() {
                     "plural"
+                } @else {
+                    "singular"
+                }
+                " pronoun"
+                @if self.singular && self.plural() {
+                    ", even when it refers to a single person"
                 }
-                " pronoun."
+                "."
             }
         }
     }
 }
 
 impl PronounSet {
+    /// Create a set from its five forms, inferring both kinds of number from them.
+    pub fn new(
+        nominative: String,
+        accusative: String,
+        determiner: String,
+        possessive: String,
+        reflexive: String,
+    ) -> Self {
+        let mut result = Self {
+            nominative,
+            accusative,
+            determiner,
+            possessive,
+            reflexive,
+            ..Default::default()
+        };
+        result.infer_number();
+        result
+    }
+
+    /// Fill in `singular` and `agreement` from the forms alone. A reflexive ending in "selves"
+    /// refers to a group, and "they" takes plural verbs no matter how many people it refers to.
+    /// This is the rule dhall/types/PronounSet.dhall documents for the sets in the database.
+    pub fn infer_number(&mut self) {
+        self.singular = !self.reflexive.ends_with("selves");
+        self.agreement = if self.nominative == "they" || !self.singular {
+            Number::Plural
+        } else {
+            Number::Singular
+        };
+    }
+
+    /// Get the form this set uses for a given case.
+    pub fn form(&self, case: Case) -> &str {
+        match case {
+            Case::Nominative => &self.nominative,
+            Case::Accusative => &self.accusative,
+            Case::Determiner => &self.determiner,
+            Case::Possessive => &self.possessive,
+            Case::Reflexive => &self.reflexive,
+        }
+    }
+
+    pub fn form_mut(&mut self, case: Case) -> &mut String {
+        match case {
+            Case::Nominative => &mut self.nominative,
+            Case::Accusative => &mut self.accusative,
+            Case::Determiner => &mut self.determiner,
+            Case::Possessive => &mut self.possessive,
+            Case::Reflexive => &mut self.reflexive,
+        }
+    }
+
     pub fn url(&self) -> String {
         format!(
             "/{}/{}/{}/{}/{}",
@@ -86,7 +272,196 @@ impl PronounSet {
         format!("{}/{}", self.nominative, self.accusative)
     }
 
+    /// The grammatical number verbs take when this set is the subject.
+    pub fn number(&self) -> Number {
+        self.agreement
+    }
+
+    /// Conjugate a verb (given as its bare infinitive, e.g. "be" or "go") in the present tense so
+    /// that it agrees with this set's nominative.
+    pub fn conjugate(&self, verb: &str) -> String {
+        Verb(verb).present(self.number())
+    }
+
+    /// Contract the nominative with a verb (given as its bare infinitive), like "they're" for
+    /// "be". Returns None if the verb doesn't contract.
+    pub fn contraction(&self, verb: &str) -> Option<String> {
+        Verb(verb)
+            .contraction(self.number())
+            .map(|ending| format!("{}{ending}", self.nominative))
+    }
+
+    /// Every contraction of the nominative.
+    pub fn contractions(&self) -> Contractions {
+        let contract = |verb| self.contraction(verb).unwrap_or_default();
+
+        Contractions {
+            be: contract("be"),
+            have: contract("have"),
+            will: contract("will"),
+            would: contract("would"),
+        }
+    }
+
     pub fn plural(&self) -> bool {
-        self.determiner.ends_with('s')
+        self.agreement == Number::Plural
+    }
+}
+
+/// Turn a path like "she/her/.../herself" into a key for [PronounTrie::guess]. Empty segments
+/// and "..." match any form.
+pub fn url_to_trie_query(url: &str) -> Vec<Option<String>> {
+    url.split('/')
+        .map(|x| match x {
+            "..." | "" => None,
+            x => Some(x.to_owned()),
+        })
+        .collect()
+}
+
+/// Uppercase the first letter of a word and leave the rest alone, so names like "McKenzie" and
+/// "Jean-Luc" survive being put at the start of a sentence. The first letter is a whole grapheme,
+/// so accents written as separate combining characters stay on it.
+pub(crate) fn capitalize(word: &str) -> String {
+    let mut graphemes = word.graphemes(true);
+    match graphemes.next() {
+        Some(first) => first.to_uppercase() + graphemes.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Put a form into the shape the database is searched in: trimmed, lowercase and in Unicode NFKC,
+/// so `She`, full-width `ｓｈｅ` and an "é" typed as "e" plus a combining accent all find the same
+/// set as the plain lowercase form.
+///
+/// NFC alone would only take care of the accents: full-width letters and ligatures like "ﬁ" are
+/// their own characters in NFC, and only the compatibility decompositions of NFKC fold them into
+/// plain letters. Lowercasing can turn one character into several (the "İ" in "İt" becomes an
+/// "i" and a combining dot), so the result goes through NFC again to stay normalised.
+pub fn normalize(form: &str) -> String {
+    form.trim()
+        .nfkc()
+        .collect::<String>()
+        .to_lowercase()
+        .nfc()
+        .collect()
+}
+
+/// The trie of every set in dhall/package.dhall, for tests.
+#[cfg(test)]
+pub(crate) fn test_trie() -> PronounTrie {
+    let sets: Vec<PronounSet> = serde_dhall::from_file("./dhall/package.dhall")
+        .parse()
+        .expect("dhall/package.dhall should parse");
+    PronounTrie::build(sets)
+}
+
+/// Helpers for the code generated by `pronoun_format!`. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    use super::PronounSet;
+
+    pub fn capitalize(word: &str) -> String {
+        super::capitalize(word)
+    }
+
+    pub fn contraction(set: &PronounSet, verb: &str) -> String {
+        set.contraction(verb).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(trie: &PronounTrie, url: &str) -> Vec<String> {
+        trie.guess(&mut url_to_trie_query(url))
+            .iter()
+            .map(PronounSet::title)
+            .collect()
+    }
+
+    #[test]
+    fn inferred_number_matches_the_data() {
+        let sets: Vec<PronounSet> = serde_dhall::from_file("./dhall/package.dhall")
+            .parse()
+            .expect("dhall/package.dhall should parse");
+
+        for set in sets {
+            let mut inferred = set.clone();
+            inferred.infer_number();
+            assert_eq!(inferred.singular, set.singular, "{}", set.title());
+            assert_eq!(inferred.agreement, set.agreement, "{}", set.title());
+        }
+    }
+
+    #[test]
+    fn they_takes_plural_verbs() {
+        let set = |reflexive: &str| {
+            PronounSet::new(
+                "they".into(),
+                "them".into(),
+                "their".into(),
+                "theirs".into(),
+                reflexive.into(),
+            )
+        };
+
+        let one = set("themself");
+        assert!(one.singular);
+        assert_eq!(one.agreement, Number::Plural);
+
+        let many = set("themselves");
+        assert!(!many.singular);
+        assert_eq!(many.agreement, Number::Plural);
+
+        let xe = PronounSet::new(
+            "xe".into(),
+            "xem".into(),
+            "xyr".into(),
+            "xyrs".into(),
+            "xemself".into(),
+        );
+        assert!(xe.singular);
+        assert_eq!(xe.agreement, Number::Singular);
+    }
+
+    #[test]
+    fn normalizing() {
+        assert_eq!(normalize(" She "), "she");
+        assert_eq!(normalize("ｓｈｅ"), "she");
+        assert_eq!(normalize("ﬁ"), "fi");
+        assert_eq!(normalize("e\u{301}l"), "\u{e9}l");
+        assert_eq!(normalize("\u{c9}L"), "\u{e9}l");
+    }
+
+    #[test]
+    fn lookups_are_normalized() {
+        let trie = test_trie();
+
+        assert_eq!(titles(&trie, "she/her"), vec!["she/her"]);
+        assert_eq!(titles(&trie, "She/Her"), vec!["she/her"]);
+        assert_eq!(titles(&trie, "ＳＨＥ/ｈｅｒ"), vec!["she/her"]);
+    }
+
+    #[test]
+    fn accents_match_however_they_are_typed() {
+        let set = |nominative: &str| {
+            PronounSet::new(
+                nominative.to_string(),
+                "em".into(),
+                "eir".into(),
+                "eirs".into(),
+                "emself".into(),
+            )
+        };
+        let composed = PronounTrie::build(vec![set("\u{e9}")]);
+        let decomposed = PronounTrie::build(vec![set("e\u{301}")]);
+
+        for trie in [&composed, &decomposed] {
+            for typed in ["\u{e9}", "e\u{301}", "\u{c9}", "E\u{301}"] {
+                assert_eq!(titles(trie, typed).len(), 1, "{typed:?}");
+            }
+        }
     }
 }
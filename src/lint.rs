@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    normalize,
+    rewrite::{is_apostrophe, match_case, pick_case, words},
+    Case, Number, PronounSet, PronounTrie,
+};
+
+/// Forms that are also everyday words, like "it" for an object or "star" for a star. Using one
+/// of these for someone who doesn't use it only gets a hint, since it is probably not about them.
+pub(crate) const COMMON_WORDS: &[&str] = &[
+    "bun", "buns", "e", "hu", "it", "its", "kit", "kits", "per", "se", "si", "star", "stars",
+    "thon", "vi", "vis", "yo",
+];
+
+/// A character in a document and the set they use.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct Character {
+    pub name: String,
+    pub set: PronounSet,
+}
+
+/// How sure the linter is that a form is wrong.
+#[derive(Clone, Copy, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// A form that almost certainly refers to the character and isn't one of theirs.
+    Warning,
+    /// A form that is wrong if it refers to the character, but could easily be about something
+    /// else, like "they" for a group or "it" for an object.
+    Hint,
+}
+
+/// A pronoun that doesn't match the character it most likely refers to.
+#[derive(Clone, Serialize, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Where the form starts in the text, in bytes.
+    pub start: usize,
+    /// Where the form ends in the text, in bytes.
+    pub end: usize,
+    /// The form as it was written.
+    pub form: String,
+    pub case: Case,
+    /// The name of the character the form was taken to refer to.
+    pub character: String,
+    /// What the character's own set has in its place.
+    pub suggestion: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl PronounTrie {
+    /// Find every pronoun in some text that doesn't match the character it refers to. A pronoun
+    /// is taken to refer to the character mentioned by name most recently in the same paragraph,
+    /// and forms that belong to any character mentioned in that paragraph are left alone since
+    /// they could be about them instead.
+    pub fn lint(&self, text: &str, characters: &[Character]) -> Vec<Diagnostic> {
+        // The first set with a form in each case it is in, in the same order as the trie.
+        let known = |word: &str| -> Vec<(Case, &PronounSet)> {
+            Case::ALL
+                .into_iter()
+                .filter_map(|case| self.with_form(case, word).first().map(|set| (case, set)))
+                .collect()
+        };
+
+        let words = words(text);
+        let adjacent = |i: usize, j: usize| text[words[i].end..words[j].start].trim().is_empty();
+        let mut mentioned: Vec<&Character> = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        for (i, range) in words.iter().enumerate() {
+            let word = &text[range.clone()];
+
+            if i > 0 && text[words[i - 1].end..range.start].matches('\n').count() >= 2 {
+                mentioned.clear();
+            }
+
+            // Names count with a possessive on them too, as in "Alex's".
+            let base = word.split(is_apostrophe).next().unwrap_or(word);
+            if let Some(character) = characters
+                .iter()
+                .find(|character| character.name.split_whitespace().any(|part| part == base))
+            {
+                mentioned.retain(|other| other.name != character.name);
+                mentioned.push(character);
+                continue;
+            }
+
+            let Some(referent) = mentioned.last() else {
+                continue;
+            };
+            let before = (i > 0 && adjacent(i - 1, i)).then(|| &text[words[i - 1].clone()]);
+            let after =
+                (i + 1 < words.len() && adjacent(i, i + 1)).then(|| &text[words[i + 1].clone()]);
+
+            // Contractions like "she's" are checked by the pronoun in front of the apostrophe.
+            let forms = known(word);
+            let (pronoun, case, suggestion, source) = if !forms.is_empty() {
+                let cases: Vec<Case> = forms.iter().map(|(case, _)| *case).collect();
+                let case = pick_case(&cases, before, after);
+                let (_, source) = forms
+                    .iter()
+                    .find(|(other, _)| *other == case)
+                    .expect("the case was picked from these forms");
+                (word, case, referent.set.form(case).to_string(), *source)
+            } else if let Some((source, (contracted, _))) = self
+                .with_form(Case::Nominative, base)
+                .first()
+                .and_then(|source| Some((source, source.contract(word, after, &referent.set)?)))
+            {
+                (base, Case::Nominative, contracted, source)
+            } else {
+                continue;
+            };
+
+            let theirs = |character: &&Character| {
+                Case::ALL
+                    .into_iter()
+                    .any(|case| normalize(character.set.form(case)) == normalize(pronoun))
+            };
+            if mentioned.iter().any(theirs) {
+                continue;
+            }
+
+            let common = COMMON_WORDS.contains(&&*normalize(pronoun));
+            let severity = if common || source.number() == Number::Plural {
+                Severity::Hint
+            } else {
+                Severity::Warning
+            };
+            let suggestion = match_case(word, &suggestion);
+
+            diagnostics.push(Diagnostic {
+                start: range.start,
+                end: range.end,
+                form: word.to_string(),
+                case,
+                character: referent.name.clone(),
+                message: format!(
+                    "{} uses {}, so this should probably be \"{suggestion}\"",
+                    referent.name,
+                    referent.set.title()
+                ),
+                suggestion,
+                severity,
+            });
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn character(name: &str, key: &[&str]) -> Character {
+        let mut key = key.iter().map(|form| Some(form.to_string())).collect();
+        Character {
+            name: name.to_string(),
+            set: crate::test_trie().guess(&mut key).remove(0),
+        }
+    }
+
+    fn spans(diagnostics: &[Diagnostic]) -> Vec<(usize, usize, &str, &str, Severity)> {
+        diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.start,
+                    d.end,
+                    d.form.as_str(),
+                    d.suggestion.as_str(),
+                    d.severity,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wrong_pronouns() {
+        let trie = crate::test_trie();
+        let alex = character("Alex", &["xe"]);
+        let text = "Alex left. She said hi to her friend.";
+
+        let diagnostics = trie.lint(text, &[alex]);
+        assert_eq!(
+            spans(&diagnostics),
+            [
+                (11, 14, "She", "Xe", Severity::Warning),
+                (26, 29, "her", "xyr", Severity::Warning),
+            ]
+        );
+        assert_eq!(&text[11..14], "She");
+        assert_eq!(diagnostics[1].case, Case::Determiner);
+        assert_eq!(diagnostics[0].character, "Alex");
+    }
+
+    #[test]
+    fn contractions_and_hints() {
+        let trie = crate::test_trie();
+        let alex = character("Alex", &["xe"]);
+        let text = "Alex waved. She\u{2019}s sure they left.";
+
+        assert_eq!(
+            spans(&trie.lint(text, &[alex])),
+            [
+                (12, 19, "She\u{2019}s", "Xe\u{2019}s", Severity::Warning),
+                (25, 29, "they", "xe", Severity::Hint),
+            ]
+        );
+    }
+
+    #[test]
+    fn right_pronouns_and_other_characters() {
+        let trie = crate::test_trie();
+        let characters = [character("Alex", &["xe"]), character("Sam Smith", &["she"])];
+        let alex = &characters[..1];
+
+        assert!(trie
+            .lint("Alex said xe would bring xyr keys.", alex)
+            .is_empty());
+        assert!(trie.lint("She left before anyone came.", alex).is_empty());
+        assert!(trie
+            .lint("Alex met Sam. She said hi.", &characters)
+            .is_empty());
+
+        let text = "Sam left.\n\nAlex stayed. She said hi.";
+        assert_eq!(
+            spans(&trie.lint(text, &characters)),
+            [(24, 27, "She", "Xe", Severity::Warning)]
+        );
+        assert_eq!(&text[24..27], "She");
+    }
+}
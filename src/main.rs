@@ -1,15 +1,30 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, RawQuery, State},
     http::StatusCode,
-    routing::get,
+    response::Redirect,
+    routing::{get, post},
     Json, Router,
 };
 use axum_extra::routing::SpaRouter;
-use maud::{html, Markup, DOCTYPE};
-use serde::Serialize;
-use std::{net::SocketAddr, sync::Arc, sync::Mutex};
+use axum_macros::FromRef;
+use maud::{html, Markup, PreEscaped, DOCTYPE};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, sync::Mutex};
 
-use xe_pronouns::{PronounSet, PronounTrie};
+use xe_pronouns::{
+    clean_name, normalize, url_to_trie_query, Candidate, Character, Completion, Contractions,
+    Corpus, Declaration, Diagnostic, Equivalent, EquivalentSource, FormMatch, Language,
+    LanguageSource, Languages, PackSource, Policy, Preference, PronounProfile, PronounSet,
+    PronounTrie, Rewrite, Severity, Suggestion, TermPreferences, Terms,
+};
+
+#[derive(Clone, FromRef)]
+struct AppState {
+    prons: Arc<PronounTrie>,
+    corpus: Arc<Corpus>,
+    terms: Arc<Terms>,
+    languages: Arc<Languages>,
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -17,6 +32,25 @@ async fn main() -> anyhow::Result<()> {
 
     let pron_trie = PronounTrie::build(pronouns);
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("lint") => return lint_command(&pron_trie, &args[1..]),
+        Some("neutralize") => return neutralize_command(&pron_trie, &args[1..]),
+        _ => {}
+    }
+
+    let packs: Vec<PackSource> = serde_dhall::from_file("./dhall/examples.dhall").parse()?;
+    let corpus = Corpus::new(packs).map_err(anyhow::Error::msg)?;
+
+    let terms: Terms = serde_dhall::from_file("./dhall/terms.dhall").parse()?;
+
+    let languages: Vec<LanguageSource> =
+        serde_dhall::from_file("./dhall/languages.dhall").parse()?;
+    let equivalents: Vec<EquivalentSource> =
+        serde_dhall::from_file("./dhall/equivalents.dhall").parse()?;
+    let languages =
+        Languages::new(languages, equivalents, &pron_trie).map_err(anyhow::Error::msg)?;
+
     let files = SpaRouter::new("/static/css", env!("XESS_PATH"));
 
     let app = Router::new()
@@ -24,6 +58,16 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/all", get(all_pronouns_json))
         .route("/api/docs", get(api_docs))
         .route("/api/lookup/*pronoun", get(guess_pronouns_json))
+        .route("/api/parse", get(parse_pronouns_json))
+        .route("/api/complete", get(complete_json))
+        .route("/api/form/:word", get(form_json))
+        .route("/api/preference/*pronoun", get(preference_json))
+        .route("/api/examples/*pronoun", get(examples_json))
+        .route("/api/equivalents/*pronoun", get(equivalents_json))
+        .route("/api/rewrite", post(rewrite_json))
+        .route("/api/lint", post(lint_json))
+        .route("/api/analyze", post(analyze_json))
+        .route("/api/neutralize", post(neutralize_json))
         .route(
             "/api/exact/:nominative/:accusative/:determiner/:possessive/:reflexive",
             get(exact_pronouns_json),
@@ -31,9 +75,19 @@ async fn main() -> anyhow::Result<()> {
         .route("/pronoun-list", get(all_pronouns))
         .route("/", get(handler))
         .route("/they", get(they))
+        .route("/noun/:word", get(nounself))
+        .route("/any", get(any))
+        .route("/ask", get(ask))
+        .route("/name/:name", get(name))
+        .route("/search", get(search))
         .route("/*pronoun", get(guess_pronouns))
         .merge(files)
-        .with_state(Arc::new(pron_trie));
+        .with_state(AppState {
+            prons: Arc::new(pron_trie),
+            corpus: Arc::new(corpus),
+            terms: Arc::new(terms),
+            languages: Arc::new(languages),
+        });
 
     // run it
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
@@ -56,53 +110,843 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Lint a file for misgendering from the command line:
+/// `xe_pronouns lint chapter.md Alex=xe/xem Sam=she/her`. Use `-` as the file to read stdin.
+fn lint_command(prons: &PronounTrie, args: &[String]) -> anyhow::Result<()> {
+    let usage = "usage: xe_pronouns lint FILE NAME=PRONOUNS...";
+    let (path, declarations) = args.split_first().ok_or_else(|| anyhow::anyhow!(usage))?;
+
+    let characters = declarations
+        .iter()
+        .map(|declaration| {
+            let (name, pronoun) = declaration
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("{declaration}: expected NAME=PRONOUNS"))?;
+            let set = prons
+                .resolve(pronoun)
+                .ok_or_else(|| unknown(prons, pronoun))?;
+            Ok(Character {
+                name: name.to_string(),
+                set,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let text = read_input(path)?;
+    let diagnostics = prons.lint(&text, &characters);
+    for diagnostic in &diagnostics {
+        let (line, column) = line_column(&text, diagnostic.start);
+        let severity = match diagnostic.severity {
+            Severity::Warning => "warning",
+            Severity::Hint => "hint",
+        };
+        println!("{path}:{line}:{column}: {severity}: {}", diagnostic.message);
+    }
+
+    // Hints are too likely to be about someone else to fail on.
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Warning)
+    {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Suggest neutral replacements for gendered language in a file:
+/// `xe_pronouns neutralize README.md`. The pronouns to use instead of "he or she" can be given
+/// after the file, and default to they/them.
+fn neutralize_command(prons: &PronounTrie, args: &[String]) -> anyhow::Result<()> {
+    let usage = "usage: xe_pronouns neutralize FILE [PRONOUNS]";
+    let path = args.first().ok_or_else(|| anyhow::anyhow!(usage))?;
+    let set = match args.get(1) {
+        Some(pronoun) => prons
+            .resolve(pronoun)
+            .ok_or_else(|| unknown(prons, pronoun))?,
+        None => prons
+            .neutral()
+            .ok_or_else(|| anyhow::anyhow!("they/them is missing from my database"))?,
+    };
+
+    let text = read_input(path)?;
+    let suggestions = set.neutralize(&text);
+    for suggestion in &suggestions {
+        let (line, column) = line_column(&text, suggestion.start);
+        println!("{path}:{line}:{column}: {}", suggestion.message);
+    }
+
+    if !suggestions.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// The error for a pronoun a subcommand can't find, with the closest sets as suggestions.
+fn unknown(prons: &PronounTrie, pronoun: &str) -> anyhow::Error {
+    let suggestions: Vec<String> = did_you_mean(prons, pronoun)
+        .iter()
+        .map(|set| set.url().trim_start_matches('/').to_string())
+        .collect();
+
+    if suggestions.is_empty() {
+        anyhow::anyhow!("can't find {pronoun} in my database")
+    } else {
+        anyhow::anyhow!(
+            "can't find {pronoun} in my database, did you mean {}?",
+            suggestions.join(", ")
+        )
+    }
+}
+
+/// Read a file for a subcommand, or stdin if the path is `-`.
+fn read_input(path: &str) -> std::io::Result<String> {
+    if path == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
+/// The 1-based line and column a byte offset is at, counting columns in characters.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let line_start = text[..offset].rfind('\n').map_or(0, |at| at + 1);
+    let line = text[..offset].matches('\n').count() + 1;
+    let column = text[line_start..offset].chars().count() + 1;
+    (line, column)
+}
+
 async fn health() -> String {
     "OK".into()
 }
 
-async fn all_pronouns_json(State(prons): State<Arc<PronounTrie>>) -> Json<Vec<PronounSet>> {
-    Json(prons.gather())
+#[derive(Deserialize, Default, Debug)]
+pub struct SetQuery {
+    #[serde(default)]
+    pub contractions: bool,
+}
+
+/// A PronounSet as the API returns it, with its contractions if they were asked for and terms if
+/// any were given.
+#[derive(Serialize, Debug)]
+pub struct ApiPronounSet {
+    #[serde(flatten)]
+    pub set: PronounSet,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contractions: Option<Contractions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terms: Option<Terms>,
+}
+
+impl ApiPronounSet {
+    /// Sets don't carry terms of their own, so terms are only included when the request gives
+    /// some. The ones it leaves out come from dhall/terms.dhall.
+    fn new(
+        set: PronounSet,
+        query: &SetQuery,
+        terms: &Terms,
+        preferences: &TermPreferences,
+    ) -> Self {
+        let contractions = query.contractions.then(|| set.contractions());
+        let terms = (!preferences.is_empty()).then(|| terms.with(preferences));
+        Self {
+            set,
+            contractions,
+            terms,
+        }
+    }
+}
+
+async fn all_pronouns_json(
+    Query(query): Query<SetQuery>,
+    Query(preferences): Query<TermPreferences>,
+    State(prons): State<Arc<PronounTrie>>,
+    State(terms): State<Arc<Terms>>,
+) -> Json<Vec<ApiPronounSet>> {
+    Json(
+        prons
+            .gather()
+            .into_iter()
+            .map(|set| ApiPronounSet::new(set, &query, &terms, &preferences))
+            .collect(),
+    )
 }
 
 async fn exact_pronouns_json(Path(ps): Path<PronounSet>) -> Json<PronounSet> {
     let mut ps = ps.clone();
-    ps.singular = true;
+    ps.infer_number();
     Json(ps)
 }
 
 #[derive(Serialize, Debug)]
 pub struct Error {
     pub message: String,
+    /// Sets close to a pronoun that couldn't be found.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<PronounSet>,
+}
+
+/// The 404 for a pronoun the API can't find, with the closest sets as suggestions.
+fn not_found(prons: &PronounTrie, pronoun: &str) -> (StatusCode, Json<Error>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(Error {
+            message: format!("can't find {pronoun} in my database"),
+            suggestions: did_you_mean(prons, pronoun),
+        }),
+    )
 }
 
 async fn guess_pronouns_json(
     Path(pronoun): Path<String>,
+    Query(query): Query<SetQuery>,
+    Query(preferences): Query<TermPreferences>,
     State(prons): State<Arc<PronounTrie>>,
-) -> Result<(StatusCode, Json<Vec<PronounSet>>), (StatusCode, Json<Error>)> {
-    let mut key = url_to_trie_query(pronoun.clone());
+    State(terms): State<Arc<Terms>>,
+) -> Result<(StatusCode, Json<Vec<ApiPronounSet>>), (StatusCode, Json<Error>)> {
+    let mut key = url_to_trie_query(&pronoun);
     let guessed = prons.guess(&mut key);
 
     if !guessed.is_empty() {
-        Ok((StatusCode::OK, Json(guessed)))
+        Ok((
+            StatusCode::OK,
+            Json(
+                guessed
+                    .into_iter()
+                    .map(|set| ApiPronounSet::new(set, &query, &terms, &preferences))
+                    .collect(),
+            ),
+        ))
     } else {
-        Err((
+        Err(not_found(&prons, &pronoun))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ParseQuery {
+    pub q: String,
+}
+
+async fn parse_pronouns_json(
+    Query(query): Query<ParseQuery>,
+    State(prons): State<Arc<PronounTrie>>,
+) -> Json<Declaration> {
+    Json(prons.parse_declaration(&query.q))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RewriteRequest {
+    pub text: String,
+    pub from: String,
+    pub to: String,
+}
+
+async fn form_json(
+    Path(word): Path<String>,
+    State(prons): State<Arc<PronounTrie>>,
+) -> Result<Json<Vec<FormMatch>>, (StatusCode, Json<Error>)> {
+    let found = prons.by_form(&word);
+
+    if found.is_empty() {
+        return Err((
             StatusCode::NOT_FOUND,
             Json(Error {
-                message: format!("can't find {pronoun} in my database"),
+                message: format!("can't find {word} in my database"),
+                suggestions: Vec::new(),
             }),
-        ))
+        ));
+    }
+
+    Ok(Json(found))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CompleteQuery {
+    pub q: String,
+}
+
+/// How many completions the search box gets at most.
+const MAX_COMPLETIONS: usize = 10;
+
+async fn complete_json(
+    Query(query): Query<CompleteQuery>,
+    State(prons): State<Arc<PronounTrie>>,
+) -> Json<Vec<Completion>> {
+    let typed: Vec<String> = query
+        .q
+        .trim_start_matches('/')
+        .split('/')
+        .map(ToString::to_string)
+        .collect();
+
+    let mut completions = prons.complete(&typed);
+    completions.truncate(MAX_COMPLETIONS);
+    Json(completions)
+}
+
+async fn rewrite_json(
+    State(prons): State<Arc<PronounTrie>>,
+    Json(request): Json<RewriteRequest>,
+) -> Result<Json<Rewrite>, (StatusCode, Json<Error>)> {
+    let resolve = |pronoun: &str| {
+        prons
+            .resolve(pronoun)
+            .ok_or_else(|| not_found(&prons, pronoun))
+    };
+    let from = resolve(&request.from)?;
+    let to = resolve(&request.to)?;
+
+    Ok(Json(from.rewrite(&request.text, &to)))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LintRequest {
+    pub text: String,
+    /// Each character's name and the pronouns they use, like "Alex": "xe/xem".
+    pub characters: HashMap<String, String>,
+}
+
+async fn lint_json(
+    State(prons): State<Arc<PronounTrie>>,
+    Json(request): Json<LintRequest>,
+) -> Result<Json<Vec<Diagnostic>>, (StatusCode, Json<Error>)> {
+    let characters = request
+        .characters
+        .into_iter()
+        .map(|(name, pronoun)| match prons.resolve(&pronoun) {
+            Some(set) => Ok(Character { name, set }),
+            None => Err(not_found(&prons, &pronoun)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(prons.lint(&request.text, &characters)))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AnalyzeRequest {
+    pub text: String,
+}
+
+async fn analyze_json(
+    State(prons): State<Arc<PronounTrie>>,
+    Json(request): Json<AnalyzeRequest>,
+) -> Json<Vec<Candidate>> {
+    Json(prons.analyze(&request.text))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NeutralizeRequest {
+    pub text: String,
+    /// The pronouns to use instead of "he or she", they/them if this is missing.
+    pub pronouns: Option<String>,
+}
+
+async fn neutralize_json(
+    State(prons): State<Arc<PronounTrie>>,
+    Json(request): Json<NeutralizeRequest>,
+) -> Result<Json<Vec<Suggestion>>, (StatusCode, Json<Error>)> {
+    let set = match &request.pronouns {
+        Some(pronoun) => prons
+            .resolve(pronoun)
+            .ok_or_else(|| not_found(&prons, pronoun)),
+        None => prons
+            .neutral()
+            .ok_or_else(|| not_found(&prons, "they/them")),
+    }?;
+
+    Ok(Json(set.neutralize(&request.text)))
+}
+
+async fn preference_json(
+    Path(pronoun): Path<String>,
+    State(prons): State<Arc<PronounTrie>>,
+) -> Result<Json<Preference>, (StatusCode, Json<Error>)> {
+    let preference = match pronoun.split('/').collect::<Vec<&str>>()[..] {
+        ["any"] => Preference::Any,
+        ["ask"] => Preference::Ask,
+        ["name", name] => match clean_name(name) {
+            Some(name) => Preference::Name { name },
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(Error {
+                        message: format!("{name:?} isn't a name that can be used"),
+                        suggestions: Vec::new(),
+                    }),
+                ))
+            }
+        },
+        _ => match prons.resolve(&pronoun) {
+            Some(set) => Preference::Pronouns(set),
+            None => return Err(not_found(&prons, &pronoun)),
+        },
+    };
+
+    Ok(Json(preference))
+}
+
+async fn examples_json(
+    Path(pronoun): Path<String>,
+    Query(query): Query<PageQuery>,
+    State(prons): State<Arc<PronounTrie>>,
+    State(corpus): State<Arc<Corpus>>,
+) -> Result<Json<Vec<String>>, (StatusCode, Json<Error>)> {
+    let Some(set) = prons.resolve(&pronoun) else {
+        return Err(not_found(&prons, &pronoun));
+    };
+
+    let name = query.name.as_deref().and_then(clean_name);
+    Ok(Json(
+        corpus
+            .get(query.pack.as_deref())
+            .examples(&set, name.as_deref())
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    ))
+}
+
+async fn equivalents_json(
+    Path(pronoun): Path<String>,
+    State(prons): State<Arc<PronounTrie>>,
+    State(languages): State<Arc<Languages>>,
+) -> Result<Json<Vec<Equivalent>>, (StatusCode, Json<Error>)> {
+    let Some(set) = prons.resolve(&pronoun) else {
+        return Err(not_found(&prons, &pronoun));
+    };
+
+    Ok(Json(languages.equivalents(&set).to_vec()))
+}
+
+async fn any(State(corpus): State<Arc<Corpus>>) -> Markup {
+    base(
+        Some("Any pronouns"),
+        html! { (Preference::Any.page(corpus.get(None))) },
+    )
+}
+
+async fn ask(State(corpus): State<Arc<Corpus>>) -> Markup {
+    base(
+        Some("Ask for pronouns"),
+        html! { (Preference::Ask.page(corpus.get(None))) },
+    )
+}
+
+async fn name(
+    Path(name): Path<String>,
+    Query(query): Query<PageQuery>,
+    State(corpus): State<Arc<Corpus>>,
+) -> (StatusCode, Markup) {
+    let Some(name) = clean_name(&name) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            base(
+                Some("No name given"),
+                html! {
+                    p {
+                        "Put the name to use at the end of the URL, like "
+                        a href="/name/Alex" { code { "/name/Alex" } }
+                        "."
+                    }
+                },
+            ),
+        );
+    };
+
+    let title = format!("No pronouns, use {name}");
+    (
+        StatusCode::OK,
+        base(
+            Some(&title),
+            html! { (Preference::Name { name }.page(corpus.get(query.pack.as_deref()))) },
+        ),
+    )
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+}
+
+/// Where the search form on the index page goes without JavaScript: send them to the page for
+/// whatever they typed, and let that page work out what they meant.
+async fn search(Query(query): Query<SearchQuery>) -> Redirect {
+    let segments: Vec<String> = query
+        .q
+        .split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(encode_segment)
+        .collect();
+
+    if segments.is_empty() {
+        return Redirect::to("/pronoun-list");
+    }
+
+    Redirect::to(&format!("/{}", segments.join("/")))
+}
+
+/// Percent-encode everything in a path segment that can't appear in one as it is, so that
+/// `:or` and `peh's` stay readable but slashes, spaces and non-ASCII text are escaped.
+fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z'
+            | b'A'..=b'Z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'='
+            | b':'
+            | b'@' => (byte as char).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+async fn they(
+    raw_query: RawQuery,
+    query: Query<PageQuery>,
+    preferences: Query<TermPreferences>,
+    prons: State<Arc<PronounTrie>>,
+    corpus: State<Arc<Corpus>>,
+    languages: State<Arc<Languages>>,
+) -> (StatusCode, Markup) {
+    pronoun_page(
+        raw_query,
+        Path("they/.../themselves".to_string()),
+        query,
+        preferences,
+        prons,
+        corpus,
+        languages,
+    )
+    .await
+}
+
+async fn nounself(
+    RawQuery(raw_query): RawQuery,
+    Path(word): Path<String>,
+    Query(query): Query<PageQuery>,
+    Query(preferences): Query<TermPreferences>,
+    State(corpus): State<Arc<Corpus>>,
+) -> (StatusCode, Markup) {
+    let name = query.name.as_deref().and_then(clean_name);
+    let pack = corpus.get(query.pack.as_deref());
+    let Some(ps) = PronounSet::nounself(&word) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            base(
+                Some("No word given"),
+                html! {
+                    p {
+                        "A nounself set needs a word or emoji to be built from, like "
+                        a href="/noun/star" { code { "/noun/star" } }
+                        "."
+                    }
+                },
+            ),
+        );
+    };
+    let title = ps.title();
+
+    (
+        StatusCode::OK,
+        base(
+            Some(&title),
+            html! {
+                p {
+                    strong { "Generated: " }
+                    "this nounself set was built from "
+                    em { (ps.nominative) }
+                    " by following the usual spelling conventions. It is not in the database."
+                }
+                (ps.page(pack, name.as_deref()))
+                (preferences)
+                (pack_links(&corpus, raw_query.as_deref()))
+            },
+        ),
+    )
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PageQuery {
+    /// The name to introduce the person with in the example sentences.
+    pub name: Option<String>,
+    /// Which pack of example sentences to use.
+    pub pack: Option<String>,
+    pub policy: Option<Policy>,
+    /// Comma-separated weights, one per set.
+    pub weights: Option<String>,
+    /// Comma-separated contexts, one per set.
+    pub contexts: Option<String>,
+}
+
+/// How many typos a pronoun can have and still get "did you mean" suggestions.
+const MAX_TYPOS: usize = 2;
+
+/// How many "did you mean" suggestions to show at most.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Find the sets closest to a URL fragment that isn't in the database, for "did you mean" links.
+fn did_you_mean(prons: &PronounTrie, pronoun: &str) -> Vec<PronounSet> {
+    prons
+        .fuzzy(&url_to_trie_query(pronoun), MAX_TYPOS)
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|found| found.set)
+        .collect()
+}
+
+/// A "did you mean" paragraph linking to each suggestion, or nothing if there aren't any.
+fn did_you_mean_links(suggestions: &[PronounSet]) -> Markup {
+    html! {
+        @if !suggestions.is_empty() {
+            p {
+                strong { "Did you mean: " }
+                @for (i, set) in suggestions.iter().enumerate() {
+                    @if i > 0 {
+                        ", "
+                    }
+                    a href=(set.url()) { (set.url().trim_start_matches('/')) }
+                }
+                "?"
+            }
+        }
+    }
+}
+
+/// Split a profile URL like "she/her/:or/they/them" into its sets, each with its weight and
+/// context from the comma-separated `weights` and `contexts`. Missing or unparseable weights are
+/// 1, and [PronounProfile::push] takes care of ones that aren't positive and finite.
+fn profile_parts<'a>(
+    pronoun: &'a str,
+    weights: &str,
+    contexts: &str,
+) -> Vec<(&'a str, f64, Option<String>)> {
+    let mut weights = weights.split(',').map(|x| x.trim().parse::<f64>().ok());
+    let mut contexts = contexts.split(',').map(|x| x.trim().to_string());
+
+    pronoun
+        .split("/:or/")
+        .map(|part| {
+            let weight = weights.next().flatten().unwrap_or(1.0);
+            let context = contexts.next().filter(|x| !x.is_empty());
+            (part.trim_matches('/'), weight, context)
+        })
+        .collect()
+}
+
+async fn profile_pronouns(
+    pronoun: String,
+    query: PageQuery,
+    preferences: &TermPreferences,
+    raw_query: Option<&str>,
+    prons: &PronounTrie,
+    corpus: &Corpus,
+) -> (StatusCode, Markup) {
+    let pack = corpus.get(query.pack.as_deref());
+    let name = query.name.as_deref().and_then(clean_name);
+    let mut profile = PronounProfile::new(query.policy.unwrap_or_default());
+    let weights = query.weights.unwrap_or_default();
+    let contexts = query.contexts.unwrap_or_default();
+
+    for (part, weight, context) in profile_parts(&pronoun, &weights, &contexts) {
+        let Some(set) = prons.resolve(part) else {
+            return (
+                StatusCode::NOT_FOUND,
+                base(
+                    Some("Can't find that pronoun"),
+                    html! {
+                        p {
+                            "This service doesn't have pronouns for "
+                            (part)
+                            " on file, so it can't show them alongside the others."
+                        }
+                        (did_you_mean_links(&did_you_mean(prons, part)))
+                    },
+                ),
+            );
+        };
+
+        profile.push(set, weight, context);
+    }
+
+    let title = profile.title();
+    (
+        StatusCode::OK,
+        base(
+            Some(&title),
+            html! {
+                (profile.page(pack, name.as_deref()))
+                (preferences)
+                (pack_links(corpus, raw_query))
+            },
+        ),
+    )
+}
+
+/// The pages for sets in other languages, at /<code>/<forms>. With no forms this lists every set
+/// in the language.
+fn language_pronouns(language: &Language, pronoun: &str) -> (StatusCode, Markup) {
+    let set_list = html! {
+        ul {
+            @for set in language.sets() {
+                li { a href=(set.url()) {(set.title())} }
+            }
+        }
+    };
+
+    if pronoun.trim_matches('/').is_empty() {
+        return (StatusCode::OK, base(Some(&language.name), set_list));
+    }
+
+    let guessed = language.guess(&url_to_trie_query(pronoun));
+
+    match guessed[..] {
+        [set] => (
+            StatusCode::OK,
+            base(
+                Some(&set.title()),
+                html! {
+                    (language.page(set))
+                    p {
+                        a href=(format!("/{}", language.code)) {
+                            "More pronouns in " (language.name)
+                        }
+                    }
+                },
+            ),
+        ),
+        [] => (
+            StatusCode::NOT_FOUND,
+            base(
+                Some("Can't find that pronoun"),
+                html! {
+                    p {
+                        "This service doesn't have pronouns for "
+                        (pronoun)
+                        " in "
+                        (language.name)
+                        " on file. These are the ones it knows:"
+                    }
+                    (set_list)
+                },
+            ),
+        ),
+        _ => (
+            StatusCode::BAD_REQUEST,
+            base(
+                Some("Ambiguous pronouns detected"),
+                html! {
+                    p {
+                        "The pronoun you are looking up ("
+                        (pronoun)
+                        ") has multiple hits in the database. Please try one of the following options:"
+                    }
+                    ul {
+                        @for hit in guessed {
+                            li { a href=(hit.url()) {(hit.title())} }
+                        }
+                    }
+                },
+            ),
+        ),
     }
 }
 
-async fn they(prons: State<Arc<PronounTrie>>) -> (StatusCode, Markup) {
-    guess_pronouns(Path("they/.../themselves".to_string()), prons).await
+/// Where to redirect a set page that isn't at its canonical path, with every segment
+/// [normalize]d and the query string kept, or None if it already is.
+fn canonical_path(pronoun: &str, raw_query: Option<&str>) -> Option<String> {
+    let canonical: Vec<String> = pronoun.split('/').map(normalize).collect();
+    if canonical.join("/") == pronoun {
+        return None;
+    }
+
+    let segments: Vec<String> = canonical
+        .iter()
+        .map(|segment| encode_segment(segment))
+        .collect();
+    let location = format!("/{}", segments.join("/"));
+
+    Some(match raw_query {
+        Some(raw_query) => format!("{location}?{raw_query}"),
+        None => location,
+    })
 }
 
+/// Send pages that aren't at their canonical path, like /She/Her, to the one that is, and show
+/// the page for everything else.
 async fn guess_pronouns(
+    RawQuery(raw_query): RawQuery,
+    Path(pronoun): Path<String>,
+    query: Query<PageQuery>,
+    preferences: Query<TermPreferences>,
+    prons: State<Arc<PronounTrie>>,
+    corpus: State<Arc<Corpus>>,
+    languages: State<Arc<Languages>>,
+) -> Result<(StatusCode, Markup), Redirect> {
+    if let Some(location) = canonical_path(&pronoun, raw_query.as_deref()) {
+        return Err(Redirect::permanent(&location));
+    }
+
+    Ok(pronoun_page(
+        RawQuery(raw_query),
+        Path(pronoun),
+        query,
+        preferences,
+        prons,
+        corpus,
+        languages,
+    )
+    .await)
+}
+
+async fn pronoun_page(
+    RawQuery(raw_query): RawQuery,
     Path(pronoun): Path<String>,
+    Query(query): Query<PageQuery>,
+    Query(preferences): Query<TermPreferences>,
     State(prons): State<Arc<PronounTrie>>,
+    State(corpus): State<Arc<Corpus>>,
+    State(languages): State<Arc<Languages>>,
 ) -> (StatusCode, Markup) {
-    let mut key = url_to_trie_query(pronoun.clone());
+    let (first, rest) = pronoun.split_once('/').unwrap_or((&pronoun, ""));
+    if let Some(language) = languages.get(first) {
+        return language_pronouns(language, rest);
+    }
+
+    if pronoun.split('/').any(|x| x == ":or") {
+        return profile_pronouns(
+            pronoun,
+            query,
+            &preferences,
+            raw_query.as_deref(),
+            &prons,
+            &corpus,
+        )
+        .await;
+    }
+
+    let name = query.name.as_deref().and_then(clean_name);
+    let pack = corpus.get(query.pack.as_deref());
+
+    let mut key = url_to_trie_query(&pronoun);
     let guessed = prons.guess(&mut key);
 
     if guessed.len() > 1 {
@@ -137,7 +981,10 @@ async fn guess_pronouns(
             base(
                 Some(&title),
                 html! {
-                    (v)
+                    (v.page(pack, name.as_deref()))
+                    (preferences)
+                    (languages.equivalents_section(v))
+                    (pack_links(&corpus, raw_query.as_deref()))
                 },
             ),
         );
@@ -145,14 +992,13 @@ async fn guess_pronouns(
 
     let sp = pronoun.split('/').collect::<Vec<&str>>();
     if sp.len() == 5 {
-        let ps = PronounSet {
-            nominative: sp[0].to_string(),
-            accusative: sp[1].to_string(),
-            determiner: sp[2].to_string(),
-            possessive: sp[3].to_string(),
-            reflexive: sp[4].to_string(),
-            singular: !sp[4].ends_with('s'),
-        };
+        let ps = PronounSet::new(
+            sp[0].to_string(),
+            sp[1].to_string(),
+            sp[2].to_string(),
+            sp[3].to_string(),
+            sp[4].to_string(),
+        );
 
         let title = format!("{}/{}", ps.nominative, ps.accusative);
         return (
@@ -160,12 +1006,46 @@ async fn guess_pronouns(
             base(
                 Some(&title),
                 html! {
-                    (ps)
+                    (ps.page(pack, name.as_deref()))
+                    (preferences)
+                    (languages.equivalents_section(&ps))
+                    (pack_links(&corpus, raw_query.as_deref()))
                 },
             ),
         );
     }
 
+    // A typo of a set in the database is more likely than a new set, so only guess the missing
+    // forms when nothing is close enough to suggest.
+    let suggestions = did_you_mean(&prons, &pronoun);
+
+    if suggestions.is_empty() {
+        if let Some(inference) = prons.infer(&url_to_trie_query(&pronoun)) {
+            let ps = inference.set;
+            let title = format!("{}/{}", ps.nominative, ps.accusative);
+            return (
+                StatusCode::OK,
+                base(
+                    Some(&title),
+                    html! {
+                        p {
+                            strong { "Inferred: " }
+                            "this set isn't in the database, so the missing forms were guessed from the pronouns that are ("
+                            (format!("{:.0}%", inference.confidence * 100.0))
+                            " confidence). If any of them are wrong, spell out all five forms like "
+                            a href=(ps.url()) { code { (ps.url()) } }
+                            "."
+                        }
+                        (ps.page(pack, name.as_deref()))
+                        (preferences)
+                        (languages.equivalents_section(&ps))
+                        (pack_links(&corpus, raw_query.as_deref()))
+                    },
+                ),
+            );
+        }
+    }
+
     (
         StatusCode::NOT_FOUND,
         base(
@@ -178,12 +1058,16 @@ async fn guess_pronouns(
                     a href="https://pony.social/@cadey" { "@cadey@pony.social" }
                     " for help."
                 }
+                (did_you_mean_links(&suggestions))
             },
         ),
     )
 }
 
-async fn all_pronouns(State(prons): State<Arc<PronounTrie>>) -> Markup {
+async fn all_pronouns(
+    State(prons): State<Arc<PronounTrie>>,
+    State(languages): State<Arc<Languages>>,
+) -> Markup {
     let pronouns = prons.gather();
     let dsp = pronouns.iter().map(|v| (v.title(), v.url()));
 
@@ -198,6 +1082,15 @@ async fn all_pronouns(State(prons): State<Arc<PronounTrie>>) -> Markup {
                 }
             }
 
+            @for language in languages.all() {
+                h2 { (language.name) }
+                ul {
+                    @for set in language.sets() {
+                        li { a href=(set.url()) {(set.title())} }
+                    }
+                }
+            }
+
             p {
                 "If your pronouns are not listed here, you can construct a custom URL like this:"
                     br;br;
@@ -226,7 +1119,7 @@ async fn api_docs() -> Markup {
 
             h3 { "PronounSet type" }
             p {
-                "The core datatype of the API is the PronounSet. It contains information on all the grammatical cases for each pronoun set. It always has five fields that are as follows:"
+                "The core datatype of the API is the PronounSet. It contains information on all the grammatical cases for each pronoun set. It always has the following fields:"
                 dl {
                     dt { "nominative" }
                     dd { "The nominative case or subject form of a pronoun. This is the case that is used when the person or object being referred to is the subject of the sentence." }
@@ -239,14 +1132,22 @@ async fn api_docs() -> Markup {
                     dt { "reflexive" }
                     dd { "The reflexive case. This is the case used when one is referring to themselves." }
                     dt { "singular" }
-                    dd { "This is true if the pronoun should be used in a singular way. This is false if it should be used in a plural way." }
+                    dd { "This is true if the pronoun refers to a single person (\"themself\"). This is false if it refers to a group (\"themselves\")." }
+                    dt { "agreement" }
+                    dd {
+                        "Either "
+                        code { "\"singular\"" }
+                        " or "
+                        code { "\"plural\"" }
+                        ". This is the number verbs take when the pronoun is the subject of a sentence. Singular \"they\" still takes plural verbs (\"they are\")."
+                    }
                 }
                 "PronounSet responses are only returned when the HTTP status is 200."
             }
             h4 { "Example" }
             pre {
                 code {
-                    "{\n  \"nominative\": \"she\",\n  \"accusative\": \"her\",\n  \"determiner\": \"her\",\n  \"possessive\": \"hers\",\n  \"reflexive\": \"herself\",\n  \"singular\": true\n}"
+                    "{\n  \"nominative\": \"she\",\n  \"accusative\": \"her\",\n  \"determiner\": \"her\",\n  \"possessive\": \"hers\",\n  \"reflexive\": \"herself\",\n  \"singular\": true,\n  \"agreement\": \"singular\"\n}"
                 }
             }
 
@@ -255,6 +1156,12 @@ async fn api_docs() -> Markup {
                 "Sometimes the service may return an error if it can't find what you're asking it. This error type will only contain a field named "
                 code { "message" }
                 " that contains a human-readable message to explain the failure. This will accompany a non-200 response."
+                br;br;
+                "If an endpoint can't find a pronoun but there are sets that are only a couple of typos away from it, the error also has a "
+                code { "suggestions" }
+                " field with up to "
+                (MAX_SUGGESTIONS)
+                " of them, closest first. Only the pronoun pages guess the forms of a set that isn't in the database, the API never does."
             }
             h4 { "Example" }
             pre {
@@ -262,8 +1169,14 @@ async fn api_docs() -> Markup {
                     "{\n  \"message\": \"can't find she/his in my database\"\n}"
                 }
             }
+            pre {
+                code {
+                    "curl https://pronouns.within.lgbt/api/lookup/thye/them"
+                    "\n{\n  \"message\": \"can't find thye/them in my database\",\n  \"suggestions\": [\n    {\n      \"nominative\": \"they\",\n      \"accusative\": \"them\",\n      ...\n    },\n    ...\n  ]\n}"
+                }
+            }
 
-            h3 { code { "/api/all" } }
+            h3 { code { "/api/all{?contractions,honorific,parent,sibling,partner,child,person}" } }
             p {
                 "This returns all information on all pronouns in the database in a list of PronounSet values."
             }
@@ -274,7 +1187,7 @@ async fn api_docs() -> Markup {
                 }
             }
 
-            h3 { code { "/api/lookup/{pronouns*}" } }
+            h3 { code { "/api/lookup/{pronouns*}{?contractions,honorific,parent,sibling,partner,child,person}" } }
             p {
                 "This attempts to figure out which pronoun you want and returns information about each PronounSet matching that description. It returns a list of PronounSet's."
                 br;br;
@@ -288,7 +1201,356 @@ async fn api_docs() -> Markup {
             pre {
                 code {
                     "curl https://pronouns.within.lgbt/api/lookup/she"
-                    "\n[\n  {\n    \"nominative\": \"she\",\n    \"accusative\": \"her\",\n    \"determiner\": \"her\",\n    \"possessive\": \"hers\",\n    \"reflexive\": \"herself\",\n    \"singular\": true\n  }\n]"
+                    "\n[\n  {\n    \"nominative\": \"she\",\n    \"accusative\": \"her\",\n    \"determiner\": \"her\",\n    \"possessive\": \"hers\",\n    \"reflexive\": \"herself\",\n    \"singular\": true,\n    \"agreement\": \"singular\"\n  }\n]"
+                }
+            }
+
+            p {
+                "Both "
+                code { "/api/all" }
+                " and "
+                code { "/api/lookup" }
+                " will add a "
+                code { "contractions" }
+                " object to every PronounSet if you pass "
+                code { "?contractions=true" }
+                ". It has the nominative contracted with "
+                code { "be" }
+                ", "
+                code { "have" }
+                ", "
+                code { "will" }
+                " and "
+                code { "would" }
+                ", using the verb forms that agree with the set."
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl https://pronouns.within.lgbt/api/lookup/they?contractions=true"
+                    "\n[\n  {\n    \"nominative\": \"they\",\n    ...\n    \"agreement\": \"plural\",\n    \"contractions\": {\n      \"be\": \"they're\",\n      \"have\": \"they've\",\n      \"will\": \"they'll\",\n      \"would\": \"they'd\"\n    }\n  }\n]"
+                }
+            }
+
+            p {
+                "Sets don't come with any other words for the person, so there is no "
+                code { "terms" }
+                " object unless you give some with query parameters: "
+                code { "honorific" }
+                ", "
+                code { "parent" }
+                ", "
+                code { "sibling" }
+                ", "
+                code { "partner" }
+                ", "
+                code { "child" }
+                " and "
+                code { "person" }
+                ". The ones you leave out are gender neutral (\"Mx.\", \"sibling\" and so on)."
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl 'https://pronouns.within.lgbt/api/lookup/she?honorific=Ms.&sibling=sister'"
+                    "\n[\n  {\n    \"nominative\": \"she\",\n    ...\n    \"terms\": {\n      \"honorific\": \"Ms.\",\n      \"parent\": \"parent\",\n      \"sibling\": \"sister\",\n      \"partner\": \"partner\",\n      \"child\": \"child\",\n      \"person\": \"person\"\n    }\n  }\n]"
+                }
+            }
+
+            h3 { code { "/api/parse{?q}" } }
+            p {
+                "This parses a free-form pronoun declaration like the ones people put in their bios, such as "
+                code { "she/they" }
+                ", "
+                code { "he/him (ask!)" }
+                " or "
+                code { "any pronouns" }
+                ". It returns an object with the following fields:"
+                dl {
+                    dt { "sets" }
+                    dd { "A list with one entry per pronoun set mentioned. Each entry is a list of every PronounSet that could be meant." }
+                    dt { "any" }
+                    dd { "True if the person uses any pronouns." }
+                    dt { "ask" }
+                    dd { "True if the person wants to be asked." }
+                    dt { "use_name" }
+                    dd { "True if the person wants their name used instead of pronouns." }
+                    dt { "unrecognised" }
+                    dd { "A list of words in slash-separated groups, like the zhe in zhe/zher, that aren't in the database. Other words are ignored unless they are a set." }
+                }
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl 'https://pronouns.within.lgbt/api/parse?q=she/they%20(ask!)'"
+                    "\n{\n  \"sets\": [\n    [\n      {\n        \"nominative\": \"she\",\n        \"accusative\": \"her\",\n        \"determiner\": \"her\",\n        \"possessive\": \"hers\",\n        \"reflexive\": \"herself\",\n        \"singular\": true,\n        \"agreement\": \"singular\"\n      }\n    ],\n    [ ... ]\n  ],\n  \"any\": false,\n  \"ask\": true,\n  \"use_name\": false,\n  \"unrecognised\": []\n}"
+                }
+            }
+
+            h3 { code { "/api/form/{word}" } }
+            p {
+                "This finds every pronoun set that has a word as one of its forms, in any case. It returns a list of objects with the "
+                code { "set" }
+                " and the "
+                code { "case" }
+                " the word is in, which is one of "
+                code { "nominative" }
+                ", "
+                code { "accusative" }
+                ", "
+                code { "determiner" }
+                ", "
+                code { "possessive" }
+                " or "
+                code { "reflexive" }
+                ". A word can be in more than one case of the same set, like \"her\" in she/her. Matches are listed by case and then alphabetically."
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl https://pronouns.within.lgbt/api/form/faers"
+                    "\n[\n  {\n    \"set\": {\n      \"nominative\": \"fae\",\n      \"accusative\": \"faer\",\n      ...\n    },\n    \"case\": \"possessive\"\n  }\n]"
+                }
+            }
+
+            h3 { code { "/api/complete{?q}" } }
+            p {
+                "This completes a partly typed pronoun set for search boxes. Every form in "
+                code { "q" }
+                " but the last has to match exactly, and the last one can be the start of a form, so "
+                code { "xe/x" }
+                " finds every set that starts with xe and then a form starting with x. It returns up to "
+                (MAX_COMPLETIONS)
+                " completions, each with the "
+                code { "set" }
+                " and the "
+                code { "url" }
+                " of its page. Sets whose last form is already complete come first, then ones with shorter forms."
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl 'https://pronouns.within.lgbt/api/complete?q=fae/fa'"
+                    "\n[\n  {\n    \"set\": {\n      \"nominative\": \"fae\",\n      \"accusative\": \"faer\",\n      ...\n    },\n    \"url\": \"/fae/faer/faer/faers/faerself\"\n  }\n]"
+                }
+            }
+
+            h3 { code { "/api/preference/{pronouns*}" } }
+            p {
+                "This is like "
+                code { "/api/lookup" }
+                " but also understands people who don't use a single pronoun set. It returns one Preference object with a "
+                code { "type" }
+                " field that is one of the following:"
+                dl {
+                    dt { code { "pronouns" } }
+                    dd { "A single PronounSet, whose fields are included in the object. " code { "/api/preference/she/her" } " returns this." }
+                    dt { code { "any" } }
+                    dd { "Any pronouns are fine. " code { "/api/preference/any" } " returns this." }
+                    dt { code { "name" } }
+                    dd { "No pronouns, the person's name (in the " code { "name" } " field) should be used instead. " code { "/api/preference/name/Alex" } " returns this." }
+                    dt { code { "ask" } }
+                    dd { "The person wants to be asked. " code { "/api/preference/ask" } " returns this." }
+                }
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl https://pronouns.within.lgbt/api/preference/name/Alex"
+                    "\n{\n  \"type\": \"name\",\n  \"name\": \"Alex\"\n}"
+                }
+            }
+
+            h3 { code { "/api/examples/{pronouns*}{?name,pack}" } }
+            p {
+                "This returns the example sentences for a pronoun set as a list of plain strings. If you pass a "
+                code { "name" }
+                ", the first sentence introduces the person by that name before switching to their pronouns. Names are limited to "
+                (xe_pronouns::MAX_NAME_LENGTH)
+                " characters. The "
+                code { "pack" }
+                " picks a themed set of sentences, one of "
+                code { "casual" }
+                " (the default), "
+                code { "workplace" }
+                " or "
+                code { "story" }
+                "."
+                br;br;
+                "The pronoun pages take the same parameters, for example "
+                a href="/she/her?name=Alex&pack=workplace" { "/she/her?name=Alex&pack=workplace" }
+                "."
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl 'https://pronouns.within.lgbt/api/examples/she/her?name=Alex'"
+                    "\n[\n  \"Alex went to the park.\",\n  \"I went with her.\",\n  \"She brought her frisbee.\",\n  ...\n]"
+                }
+            }
+
+            h3 { code { "/api/equivalents/{pronouns*}" } }
+            p {
+                "This returns sets in other languages that people who use a pronoun set often pick, with a note on how each one is used. It is the same list as the \"In other languages\" section of the set's page, and is empty if there aren't any. Each "
+                code { "set" }
+                " has the "
+                code { "language" }
+                " code and its "
+                code { "forms" }
+                ", one per case in that language, and its page is at "
+                code { "/{language}/{forms*}" }
+                "."
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl https://pronouns.within.lgbt/api/equivalents/they/them"
+                    "\n[\n  {\n    \"name\": \"Svenska\",\n    \"set\": {\n      \"language\": \"sv\",\n      \"forms\": [\"hen\", \"hen\", \"hens\"]\n    },\n    \"note\": \"Hen is in the Swedish Academy's dictionary...\"\n  },\n  ...\n]"
+                }
+            }
+
+            h3 { code { "POST /api/rewrite" } }
+            p {
+                "This rewrites text written with one pronoun set so that it uses another. Send a JSON object with the "
+                code { "text" }
+                ", the set it is written with in "
+                code { "from" }
+                " and the set to use instead in "
+                code { "to" }
+                ". Sets are written the same way as in page URLs, like "
+                code { "she/her" }
+                ". It returns an object with the following fields:"
+                dl {
+                    dt { "text" }
+                    dd { "The rewritten text. Capitalisation, punctuation and contractions are kept." }
+                    dt { "substitutions" }
+                    dd {
+                        "A list of every word that was replaced, with its byte offsets in the original text ("
+                        code { "start" }
+                        " and "
+                        code { "end" }
+                        "), the "
+                        code { "original" }
+                        " word, its "
+                        code { "replacement" }
+                        " and the "
+                        code { "case" }
+                        " it was taken to be in. Verbs that were changed to agree with the new set have no case. "
+                        code { "certain" }
+                        " is false for words like \"her\" that could be more than one form, and for verbs like \"runs\" that were only recognised by their spelling, where the replacement is a guess worth checking. A word after a plural subject like \"they\" that might be a verb is flagged but left as it is, since its spelling doesn't say whether it needs to change."
+                    }
+                }
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl -X POST https://pronouns.within.lgbt/api/rewrite -H 'Content-Type: application/json' -d '{\"text\": \"She has her keys.\", \"from\": \"she/her\", \"to\": \"they/them\"}'"
+                    "\n{\n  \"text\": \"They have their keys.\",\n  \"substitutions\": [\n    {\n      \"start\": 0,\n      \"end\": 3,\n      \"original\": \"She\",\n      \"replacement\": \"They\",\n      \"case\": \"nominative\",\n      \"certain\": true\n    },\n    ...\n  ]\n}"
+                }
+            }
+
+            h3 { code { "POST /api/lint" } }
+            p {
+                "This checks text for pronouns that don't match the people it is about. Send a JSON object with the "
+                code { "text" }
+                " and "
+                code { "characters" }
+                ", an object mapping each person's name to their pronouns (like "
+                code { "\"Alex\": \"xe/xem\"" }
+                "). Each pronoun is taken to be about whoever was mentioned by name most recently in the same paragraph. It returns a list of diagnostics with the following fields:"
+                dl {
+                    dt { "start, end" }
+                    dd { "The byte offsets of the pronoun in the text." }
+                    dt { "form" }
+                    dd { "The pronoun as it was written." }
+                    dt { "case" }
+                    dd { "Which form it was taken to be, such as " code { "determiner" } "." }
+                    dt { "character" }
+                    dd { "The name of the person it was taken to be about." }
+                    dt { "suggestion" }
+                    dd { "The form from that person's pronouns to use instead." }
+                    dt { "severity" }
+                    dd {
+                        code { "warning" }
+                        ", or "
+                        code { "hint" }
+                        " for forms like \"they\" or \"it\" that could easily be about something else."
+                    }
+                    dt { "message" }
+                    dd { "A human-readable explanation." }
+                }
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl -X POST https://pronouns.within.lgbt/api/lint -H 'Content-Type: application/json' -d '{\"text\": \"Alex left. She said hi.\", \"characters\": {\"Alex\": \"xe/xem\"}}'"
+                    "\n[\n  {\n    \"start\": 11,\n    \"end\": 14,\n    \"form\": \"She\",\n    \"case\": \"nominative\",\n    \"character\": \"Alex\",\n    \"suggestion\": \"Xe\",\n    \"severity\": \"warning\",\n    \"message\": \"Alex uses xe/xem, so this should probably be \\\"Xe\\\"\"\n  }\n]"
+                }
+            }
+
+            h3 { code { "POST /api/analyze" } }
+            p {
+                "This works out which pronoun sets a piece of text uses. Send a JSON object with the "
+                code { "text" }
+                ". It returns a list of candidates, best first, with the following fields:"
+                dl {
+                    dt { "set" }
+                    dd { "The PronounSet." }
+                    dt { "score" }
+                    dd { "How strongly the text points at this set. Each form found counts 1, split evenly between every set that has it, so \"hir\" counts a quarter towards each of the four sets that use it. Forms that are also everyday words like \"it\" count half." }
+                    dt { "cases" }
+                    dd { "Every form of the set that was found." }
+                    dt { "evidence" }
+                    dd {
+                        "Each form that was found, with its byte offsets in the text ("
+                        code { "start" }
+                        " and "
+                        code { "end" }
+                        "), the "
+                        code { "form" }
+                        " as written, its "
+                        code { "case" }
+                        " and the "
+                        code { "weight" }
+                        " it counted for."
+                    }
+                }
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl -X POST https://pronouns.within.lgbt/api/analyze -H 'Content-Type: application/json' -d '{\"text\": \"Ze brought hir frisbee.\"}'"
+                    "\n[\n  {\n    \"set\": {\n      \"nominative\": \"ze\",\n      \"accusative\": \"hir\",\n      ...\n    },\n    \"score\": 0.5,\n    \"cases\": [\"nominative\", \"determiner\"],\n    \"evidence\": [\n      {\n        \"start\": 0,\n        \"end\": 2,\n        \"form\": \"Ze\",\n        \"case\": \"nominative\",\n        \"weight\": 0.25\n      },\n      ...\n    ]\n  },\n  ...\n]"
+                }
+            }
+
+            h3 { code { "POST /api/neutralize" } }
+            p {
+                "This finds gendered language like \"he or she\", \"chairman\" and \"guys\" and suggests neutral replacements. Send a JSON object with the "
+                code { "text" }
+                " and optionally the "
+                code { "pronouns" }
+                " to use instead of pronoun pairs like \"he or she\", which default to "
+                a href="/they" { "they/them" }
+                ". It returns a list of suggestions, each with the byte offsets of the gendered words ("
+                code { "start" }
+                " and "
+                code { "end" }
+                "), the "
+                code { "original" }
+                " text, its "
+                code { "replacement" }
+                ", a human-readable "
+                code { "message" }
+                " and whether it is "
+                code { "certain" }
+                ". That is false when a verb after a pronoun pair was only recognised by its spelling, like \"runs\" in \"he or she runs\", so the replacement is worth checking."
+            }
+            h4 { "Example" }
+            pre {
+                code {
+                    "curl -X POST https://pronouns.within.lgbt/api/neutralize -H 'Content-Type: application/json' -d '{\"text\": \"Ask the chairman if he or she is free.\"}'"
+                    "\n[\n  {\n    \"start\": 8,\n    \"end\": 16,\n    \"original\": \"chairman\",\n    \"replacement\": \"chair\",\n    \"message\": \"\\\"chairman\\\" is gendered, try \\\"chair\\\"\",\n    \"certain\": true\n  },\n  {\n    \"start\": 20,\n    \"end\": 32,\n    \"original\": \"he or she is\",\n    \"replacement\": \"they are\",\n    \"message\": \"\\\"he or she is\\\" leaves people out, try \\\"they are\\\"\",\n    \"certain\": true\n  }\n]"
                 }
             }
 
@@ -306,14 +1568,36 @@ async fn api_docs() -> Markup {
             pre {
                 code {
                     "curl https://pronouns.within.lgbt/api/exact/char/char/char/chars/charself"
-                    "\n{\n  \"nominative\": \"char\",\n  \"accusative\": \"char\",\n  \"determiner\": \"char\",\n  \"possessive\": \"chars\",\n  \"reflexive\": \"charself\",\n  \"singular\": true\n}"
+                    "\n{\n  \"nominative\": \"char\",\n  \"accusative\": \"char\",\n  \"determiner\": \"char\",\n  \"possessive\": \"chars\",\n  \"reflexive\": \"charself\",\n  \"singular\": true,\n  \"agreement\": \"singular\"\n}"
                 }
             }
         },
     )
 }
 
-async fn handler() -> Markup {
+/// Fill in the search box's suggestions from /api/complete as people type. Without this the form
+/// still works, it just doesn't suggest anything.
+const SEARCH_SCRIPT: &str = r#"
+const search = document.getElementById("search");
+const completions = document.getElementById("completions");
+
+search.addEventListener("input", async () => {
+    const typed = search.value;
+    const response = await fetch("/api/complete?q=" + encodeURIComponent(typed));
+    if (!response.ok || typed !== search.value) {
+        return;
+    }
+
+    const options = (await response.json()).map(({ url }) => {
+        const option = document.createElement("option");
+        option.value = url.slice(1);
+        return option;
+    });
+    completions.replaceChildren(...options);
+});
+"#;
+
+async fn handler(State(languages): State<Arc<Languages>>) -> Markup {
     base(
         None,
         html! {
@@ -321,6 +1605,15 @@ async fn handler() -> Markup {
                 "Hello, this is a service that lets you demonstrate how various third-person pronouns are used. It will list all of the grammatical forms for each pronoun set."
             }
 
+            form action="/search" method="get" {
+                label for="search" { "Look up your pronouns: " }
+                input #search type="search" name="q" list="completions" autocomplete="off" placeholder="xe/xem";
+                datalist #completions {}
+                " "
+                button type="submit" { "Go" }
+            }
+            script { (PreEscaped(SEARCH_SCRIPT)) }
+
             a href="/pronoun-list" { "All the pronouns in the database" }
             br;
             a href="/api/docs" { "API Documentation" }
@@ -335,10 +1628,117 @@ async fn handler() -> Markup {
                 }
                 "This is a bit verbose, but it will work."
             }
+
+            p {
+                "Not everyone uses a pronoun set. There are pages for people who use "
+                a href="/any" { "any pronouns" }
+                ", people who want you to "
+                a href="/ask" { "ask" }
+                ", and people who want their "
+                a href="/name/Alex" { "name used instead" }
+                " (replace Alex with your name)."
+            }
+
+            p {
+                "If you use more than one set of pronouns, you can combine them with "
+                code { ":or" }
+                " like this:"
+                br;br;
+                code {
+                    pre {
+                        "https://pronouns.within.lgbt/she/her/:or/they/them"
+                    }
+                }
+                "By default the example sentences alternate between the sets. Add "
+                code { "?policy=prefer-first" }
+                " if you prefer the first set, or "
+                code { "?policy=context-specific&contexts=at work,with friends" }
+                " if each set belongs somewhere different. You can also weight the sets with "
+                code { "?weights=2,1" }
+                "."
+            }
+
+            p {
+                "Pronoun pages can also list an honorific and words like \"sibling\" and \"partner\" to use for you. Only the ones you pick are shown:"
+                br;br;
+                code {
+                    pre {
+                        "https://pronouns.within.lgbt/she/her?honorific=Ms.&sibling=sister"
+                    }
+                }
+                "You can set "
+                code { "honorific" }
+                ", "
+                code { "parent" }
+                ", "
+                code { "sibling" }
+                ", "
+                code { "partner" }
+                ", "
+                code { "child" }
+                " and "
+                code { "person" }
+                "."
+            }
+
+            @if !languages.all().is_empty() {
+                p {
+                    "There are also pronouns in other languages: "
+                    @for (i, language) in languages.all().iter().enumerate() {
+                        @if i > 0 {
+                            ", "
+                        }
+                        a href=(format!("/{}", language.code)) { (language.name) }
+                    }
+                    ". Their pages work the same way, like "
+                    a href="/de/xier" { code { "/de/xier" } }
+                    "."
+                }
+            }
+
+            p {
+                "If you use nounself pronouns, you can generate a set from a single word or emoji like this:"
+                br;br;
+                code {
+                    pre {
+                        "https://pronouns.within.lgbt/noun/star"
+                    }
+                }
+            }
         },
     )
 }
 
+/// Links to the same page with each of the other example packs, keeping the rest of the query
+/// string.
+fn pack_links(corpus: &Corpus, raw_query: Option<&str>) -> Markup {
+    let others: Vec<&str> = raw_query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty() && pair.split('=').next() != Some("pack"))
+        .collect();
+    let href = |name: &str| {
+        let mut pairs = others.clone();
+        let pack = format!("pack={}", encode_segment(name));
+        pairs.push(&pack);
+        format!("?{}", pairs.join("&"))
+    };
+
+    html! {
+        @if corpus.packs().len() > 1 {
+            p {
+                "More examples: "
+                @for (i, pack) in corpus.packs().iter().enumerate() {
+                    @if i > 0 {
+                        " - "
+                    }
+                    a href=(href(&pack.name)) title=(pack.description) { (pack.name) }
+                }
+            }
+        }
+    }
+}
+
 fn base(title: Option<&str>, body: Markup) -> Markup {
     html! {
         (DOCTYPE)
@@ -384,11 +1784,159 @@ fn base(title: Option<&str>, body: Markup) -> Markup {
     }
 }
 
-fn url_to_trie_query(url: String) -> Vec<Option<String>> {
-    url.split('/')
-        .map(|x| match x {
-            "..." | "" => None,
-            x => Some(x.to_owned()),
-        })
-        .collect()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corpus() -> State<Arc<Corpus>> {
+        let packs: Vec<PackSource> = serde_dhall::from_file("./dhall/examples.dhall")
+            .parse()
+            .expect("dhall/examples.dhall should parse");
+        State(Arc::new(Corpus::new(packs).unwrap()))
+    }
+
+    fn trie() -> PronounTrie {
+        let sets: Vec<PronounSet> = serde_dhall::from_file("./dhall/package.dhall")
+            .parse()
+            .expect("dhall/package.dhall should parse");
+        PronounTrie::build(sets)
+    }
+
+    #[test]
+    fn resolving_never_infers() {
+        let prons = trie();
+
+        assert!(prons.resolve("shee/her").is_none());
+        assert!(prons.infer(&url_to_trie_query("shee/her")).is_some());
+
+        let (status, Json(error)) = not_found(&prons, "shee/her");
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error.suggestions[0].title(), "she/her");
+
+        let error = unknown(&prons, "shee/her").to_string();
+        assert!(
+            error.contains("did you mean she/her/her/hers/herself"),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn profiles_with_typos_are_not_found() {
+        let (status, page) = profile_pronouns(
+            "she/her/:or/thye/them".to_string(),
+            PageQuery::default(),
+            &TermPreferences::default(),
+            None,
+            &trie(),
+            &corpus().0,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(page.into_string().contains("Did you mean"));
+    }
+
+    #[tokio::test]
+    async fn preference_pages() {
+        let page = any(corpus()).await.into_string();
+        assert!(page.contains("fine with any pronouns"));
+        let page = ask(corpus()).await.into_string();
+        assert!(page.contains("ask them which pronouns"));
+
+        let (status, page) = name(Path("Alex".into()), Query(PageQuery::default()), corpus()).await;
+        assert_eq!(status, StatusCode::OK);
+        let page = page.into_string();
+        assert!(page.contains("No pronouns, use Alex"));
+        assert!(page.contains("<em>Alex</em> went to the park."));
+
+        let (status, page) = name(Path("  ".into()), Query(PageQuery::default()), corpus()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(page.into_string().contains("No name given"));
+    }
+
+    #[test]
+    fn profile_urls() {
+        assert_eq!(
+            profile_parts("she/her/:or/they/them/", "2, x", ",at work"),
+            vec![
+                ("she/her", 2.0, None),
+                ("they/them", 1.0, Some("at work".to_string())),
+            ]
+        );
+        assert_eq!(
+            profile_parts("she/:or/they/:or/xe", "", ""),
+            vec![("she", 1.0, None), ("they", 1.0, None), ("xe", 1.0, None)]
+        );
+
+        // "inf" parses, so it is up to the profile to keep it out of the schedule.
+        let mut profile = PronounProfile::new(Policy::Alternate);
+        for (_, weight, _) in profile_parts("she/:or/he", "inf,1", "") {
+            profile.push(PronounSet::default(), weight, None);
+        }
+        assert_eq!(profile.schedule(4), vec![0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn terms_only_when_given() {
+        let terms: Terms = serde_dhall::from_file("./dhall/terms.dhall")
+            .parse()
+            .expect("dhall/terms.dhall should parse");
+        let set = trie().resolve("he/him").unwrap();
+        let query = SetQuery::default();
+
+        let api = ApiPronounSet::new(set.clone(), &query, &terms, &TermPreferences::default());
+        assert_eq!(api.terms, None);
+        assert!(!serde_json::to_string(&api).unwrap().contains("Mx."));
+
+        let preferences = TermPreferences {
+            sibling: Some("brother".to_string()),
+            ..Default::default()
+        };
+        let api = ApiPronounSet::new(set, &query, &terms, &preferences);
+        let given = api.terms.unwrap();
+        assert_eq!(given.sibling, "brother");
+        assert_eq!(given.parent, terms.parent);
+    }
+
+    #[tokio::test]
+    async fn language_pages() {
+        let sources: Vec<LanguageSource> = serde_dhall::from_file("./dhall/languages.dhall")
+            .parse()
+            .expect("dhall/languages.dhall should parse");
+        let languages = State(Arc::new(Languages::new(sources, vec![], &trie()).unwrap()));
+
+        for path in ["de/xier", "DE/xier", "de/XIER/xien"] {
+            let (status, page) = pronoun_page(
+                RawQuery(None),
+                Path(path.to_string()),
+                Query(PageQuery::default()),
+                Query(TermPreferences::default()),
+                State(Arc::new(trie())),
+                corpus(),
+                languages.clone(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK, "{path}");
+            let page = page.into_string();
+            assert!(page.contains("<td>xiem</td>"), "{path}");
+            assert!(page.contains("More pronouns in Deutsch"), "{path}");
+        }
+
+        let (status, _) = language_pronouns(languages.get("de").unwrap(), "xyz");
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn canonical_paths() {
+        assert_eq!(canonical_path("she/her", None), None);
+        assert_eq!(canonical_path("they/:or/she", None), None);
+        assert_eq!(canonical_path("She/Her", None).as_deref(), Some("/she/her"));
+        assert_eq!(
+            canonical_path("ｓｈｅ/her", Some("name=Alex")).as_deref(),
+            Some("/she/her?name=Alex")
+        );
+        assert_eq!(
+            canonical_path("E\u{301}/em", None).as_deref(),
+            Some("/%C3%A9/em")
+        );
+    }
 }
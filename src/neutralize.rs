@@ -0,0 +1,290 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use super::{
+    rewrite::{agree_verb, match_case, words},
+    Case, Number, PronounSet, PronounTrie,
+};
+
+/// Pairs of gendered pronouns that are used together to mean "anyone", like "he or she", and the
+/// case of the neutral form that replaces them. They can come in either order.
+const PAIRS: &[(&str, &str, Case)] = &[
+    ("he", "she", Case::Nominative),
+    ("him", "her", Case::Accusative),
+    ("his", "her", Case::Determiner),
+    ("his", "hers", Case::Possessive),
+    ("himself", "herself", Case::Reflexive),
+];
+
+/// Gendered words and phrases with neutral replacements. Phrases are matched word by word, so
+/// punctuation between the words stops them from matching.
+const PHRASES: &[(&[&str], &str)] = &[
+    (&["ladies", "and", "gentlemen"], "everyone"),
+    (&["you", "guys"], "you all"),
+    (&["guys"], "everyone"),
+    (&["businessman"], "businessperson"),
+    (&["businessmen"], "businesspeople"),
+    (&["businesswoman"], "businessperson"),
+    (&["businesswomen"], "businesspeople"),
+    (&["chairman"], "chair"),
+    (&["chairmen"], "chairs"),
+    (&["chairwoman"], "chair"),
+    (&["chairwomen"], "chairs"),
+    (&["congressman"], "member of Congress"),
+    (&["congresswoman"], "member of Congress"),
+    (&["fireman"], "firefighter"),
+    (&["firemen"], "firefighters"),
+    (&["forefathers"], "ancestors"),
+    (&["freshman"], "first-year student"),
+    (&["freshmen"], "first-year students"),
+    (&["mailman"], "mail carrier"),
+    (&["mailmen"], "mail carriers"),
+    (&["mankind"], "humankind"),
+    (&["manpower"], "workforce"),
+    (&["policeman"], "police officer"),
+    (&["policemen"], "police officers"),
+    (&["policewoman"], "police officer"),
+    (&["salesman"], "salesperson"),
+    (&["salesmen"], "salespeople"),
+    (&["saleswoman"], "salesperson"),
+    (&["spokesman"], "spokesperson"),
+    (&["spokesmen"], "spokespeople"),
+    (&["spokeswoman"], "spokesperson"),
+    (&["stewardess"], "flight attendant"),
+    (&["stewardesses"], "flight attendants"),
+    (&["waitress"], "server"),
+    (&["waitresses"], "servers"),
+];
+
+/// A gendered construction and a neutral way to write it.
+#[derive(Clone, Serialize, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// Where the construction starts in the text, in bytes.
+    pub start: usize,
+    /// Where the construction ends in the text, in bytes.
+    pub end: usize,
+    pub original: String,
+    pub replacement: String,
+    pub message: String,
+    /// False if a verb after a pronoun pair was taken to be a regular verb from its spelling
+    /// alone and changed to agree, so the replacement should be checked.
+    pub certain: bool,
+}
+
+impl PronounTrie {
+    /// The set gendered pronouns are replaced with when nothing else is asked for, the same
+    /// they/them as the /they page.
+    pub fn neutral(&self) -> Option<PronounSet> {
+        let mut key = vec![
+            Some("they".to_string()),
+            None,
+            None,
+            None,
+            Some("themselves".to_string()),
+        ];
+        self.guess(&mut key).pop()
+    }
+}
+
+impl PronounSet {
+    /// Find gendered language in some text, like "he or she", "chairman" and "guys", and suggest
+    /// neutral replacements. Pronoun pairs are replaced with this set's forms, and the verb right
+    /// after them is changed to agree with it, like "he or she runs" to "they run".
+    pub fn neutralize(&self, text: &str) -> Vec<Suggestion> {
+        let words = words(text);
+        let word = |i: usize| &text[words[i].clone()];
+        let mut suggestions = Vec::new();
+        let mut i = 0;
+
+        while i < words.len() {
+            if let Some((start, case, last)) = pair(&words, text, i) {
+                let mut end = words[last].end;
+                let mut replacement = self.form(case).to_string();
+                let mut certain = true;
+
+                let next = words
+                    .get(last + 1)
+                    .filter(|next| text[end..next.start].trim().is_empty());
+                if let (Case::Nominative, Some(next)) = (case, next) {
+                    if let Some((verb, verb_certain)) =
+                        agree_verb(&text[next.clone()], Number::Singular, self.number())
+                    {
+                        replacement = format!("{replacement} {verb}");
+                        end = next.end;
+                        certain = verb_certain;
+                    }
+                }
+
+                let original = &text[start..end];
+                let replacement = match_case(word(i), &replacement);
+                let mut message =
+                    format!("\"{original}\" leaves people out, try \"{replacement}\"");
+                if !certain {
+                    message.push_str(" (check the verb)");
+                }
+                suggestions.push(Suggestion {
+                    start,
+                    end,
+                    message,
+                    original: original.to_string(),
+                    replacement,
+                    certain,
+                });
+                i = last + 1;
+                continue;
+            }
+
+            if let Some((length, replacement)) = phrase(&words, text, i) {
+                let Range { start, .. } = words[i];
+                let end = words[i + length - 1].end;
+                let original = &text[start..end];
+                let replacement = match_case(word(i), replacement);
+
+                suggestions.push(Suggestion {
+                    start,
+                    end,
+                    message: format!("\"{original}\" is gendered, try \"{replacement}\""),
+                    original: original.to_string(),
+                    replacement,
+                    certain: true,
+                });
+                i += length;
+                continue;
+            }
+
+            i += 1;
+        }
+
+        suggestions
+    }
+}
+
+/// Find a pair of pronouns starting at word `i`: "he or she", "he/she", "s/he" or "(s)he", in
+/// either order. Returns where it starts in bytes (which is before the word for "(s)he"), its case
+/// and the index of its last word.
+fn pair(words: &[Range<usize>], text: &str, i: usize) -> Option<(usize, Case, usize)> {
+    let word = |j: usize| &text[words[j].clone()];
+    let gap = |j: usize| &text[words[j].end..words[j + 1].start];
+
+    if i + 1 >= words.len() {
+        return None;
+    }
+
+    if i + 2 < words.len()
+        && gap(i).trim().is_empty()
+        && gap(i + 1).trim().is_empty()
+        && word(i + 1).eq_ignore_ascii_case("or")
+    {
+        if let Some(case) = pair_case(word(i), word(i + 2)) {
+            return Some((words[i].start, case, i + 2));
+        }
+    }
+
+    if gap(i) == "/" {
+        if let Some(case) = pair_case(word(i), word(i + 1)) {
+            return Some((words[i].start, case, i + 1));
+        }
+    }
+
+    if word(i).eq_ignore_ascii_case("s") && word(i + 1).eq_ignore_ascii_case("he") {
+        if gap(i) == "/" {
+            return Some((words[i].start, Case::Nominative, i + 1));
+        }
+        if gap(i) == ")" && text[..words[i].start].ends_with('(') {
+            return Some((words[i].start - 1, Case::Nominative, i + 1));
+        }
+    }
+
+    None
+}
+
+/// The case a pair of pronouns like "him" and "her" is in, if they are one of the [PAIRS].
+fn pair_case(first: &str, second: &str) -> Option<Case> {
+    let (first, second) = (first.to_lowercase(), second.to_lowercase());
+
+    PAIRS
+        .iter()
+        .find(|(he, she, _)| (first == *he && second == *she) || (first == *she && second == *he))
+        .map(|(_, _, case)| *case)
+}
+
+/// The longest of the [PHRASES] starting at word `i`, as how many words it is and what to replace
+/// it with.
+fn phrase(words: &[Range<usize>], text: &str, i: usize) -> Option<(usize, &'static str)> {
+    PHRASES
+        .iter()
+        .filter(|(phrase, _)| {
+            i + phrase.len() <= words.len()
+                && phrase.iter().enumerate().all(|(offset, expected)| {
+                    let at = i + offset;
+                    let adjacent =
+                        offset == 0 || text[words[at - 1].end..words[at].start].trim().is_empty();
+                    adjacent && text[words[at].clone()].eq_ignore_ascii_case(expected)
+                })
+        })
+        .max_by_key(|(phrase, _)| phrase.len())
+        .map(|(phrase, replacement)| (phrase.len(), *replacement))
+}
+
+#[cfg(test)]
+mod tests {
+    fn neutralize(text: &str) -> Vec<(usize, usize, String, bool)> {
+        crate::test_trie()
+            .neutral()
+            .unwrap()
+            .neutralize(text)
+            .into_iter()
+            .map(|suggestion| {
+                (
+                    suggestion.start,
+                    suggestion.end,
+                    suggestion.replacement,
+                    suggestion.certain,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn irregular_verbs_agree_for_certain() {
+        assert_eq!(
+            neutralize("Ask the chairman if he or she is free."),
+            [
+                (8, 16, "chair".to_string(), true),
+                (20, 32, "they are".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn regular_verbs_agree_but_need_checking() {
+        let suggestions = crate::test_trie()
+            .neutral()
+            .unwrap()
+            .neutralize("If he or she runs late, s/he watches it.");
+
+        assert_eq!(suggestions[0].replacement, "they run");
+        assert!(!suggestions[0].certain);
+        assert!(suggestions[0].message.ends_with("(check the verb)"));
+        assert_eq!(suggestions[1].original, "s/he watches");
+        assert_eq!(suggestions[1].replacement, "they watch");
+        assert!(!suggestions[1].certain);
+    }
+
+    #[test]
+    fn pairs_and_phrases() {
+        assert_eq!(
+            neutralize("Thank him/her, ladies and gentlemen. (S)he will."),
+            [
+                (6, 13, "them".to_string(), true),
+                (15, 35, "everyone".to_string(), true),
+                (37, 42, "They".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            neutralize("He or she walked."),
+            [(0, 9, "They".to_string(), true)]
+        );
+    }
+}
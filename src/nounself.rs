@@ -0,0 +1,87 @@
+use super::PronounSet;
+
+impl PronounSet {
+    /// Generate a nounself set (like star/star/stars/stars/starself) from a single noun or emoji.
+    ///
+    /// The noun is used as-is for the nominative and accusative and gets "self" for the
+    /// reflexive. Both possessive forms are possessives, never plurals, following the same
+    /// conventions as the hand-written sets: "star" becomes "stars" and "kitty" keeps its "y" as
+    /// "kittys". A noun ending in a hissing sound can't take a bare "s", so it gets "'s" like
+    /// peh's ("fox" becomes "fox's"), and so do emoji and anything else that isn't a word.
+    ///
+    /// Returns None if there is no word, or it is only whitespace.
+    pub fn nounself(word: &str) -> Option<Self> {
+        let word = word.trim();
+        if word.is_empty() {
+            return None;
+        }
+
+        let hissing = ["s", "x", "z", "ch", "sh"]
+            .iter()
+            .any(|suffix| word.ends_with(suffix));
+        let possessive = if hissing || !word.chars().all(char::is_alphabetic) {
+            format!("{word}'s")
+        } else {
+            format!("{word}s")
+        };
+
+        Some(Self::new(
+            word.to_string(),
+            word.to_string(),
+            possessive.clone(),
+            possessive,
+            format!("{word}self"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(word: &str) -> String {
+        PronounSet::nounself(word).unwrap().url()
+    }
+
+    #[test]
+    fn plain_nouns() {
+        assert_eq!(url("star"), "/star/star/stars/stars/starself");
+    }
+
+    #[test]
+    fn hissing_sounds() {
+        assert_eq!(url("fox"), "/fox/fox/fox's/fox's/foxself");
+        assert_eq!(url("moss"), "/moss/moss/moss's/moss's/mossself");
+        assert_eq!(url("buzz"), "/buzz/buzz/buzz's/buzz's/buzzself");
+        assert_eq!(url("peach"), "/peach/peach/peach's/peach's/peachself");
+        assert_eq!(url("fish"), "/fish/fish/fish's/fish's/fishself");
+    }
+
+    #[test]
+    fn y_is_kept() {
+        assert_eq!(url("kitty"), "/kitty/kitty/kittys/kittys/kittyself");
+    }
+
+    #[test]
+    fn emoji() {
+        assert_eq!(url("🌟"), "/🌟/🌟/🌟's/🌟's/🌟self");
+    }
+
+    #[test]
+    fn whitespace_is_trimmed() {
+        assert_eq!(url("  star "), "/star/star/stars/stars/starself");
+    }
+
+    #[test]
+    fn empty() {
+        assert!(PronounSet::nounself("").is_none());
+        assert!(PronounSet::nounself(" \t ").is_none());
+    }
+
+    #[test]
+    fn singular() {
+        let set = PronounSet::nounself("star").unwrap();
+        assert!(set.singular);
+        assert!(!set.plural());
+    }
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+
+use super::{Case, PronounSet, PronounTrie};
+
+/// What a free-form pronoun declaration like "she/they (ask!)" says about someone.
+#[derive(Clone, Serialize, Default, Debug)]
+pub struct Declaration {
+    /// One entry per set mentioned, in the order they were written. Each entry holds every set in
+    /// the database that could be meant, so "they/them" gives both themself and themselves.
+    pub sets: Vec<Vec<PronounSet>>,
+    /// "any pronouns", "any/all" and the like.
+    pub any: bool,
+    /// "ask me", "(ask!)" and the like.
+    pub ask: bool,
+    /// "no pronouns", "use my name" and the like.
+    pub use_name: bool,
+    /// Words in slash-separated groups, like the "zhe" in "zhe/zher", that don't match anything
+    /// in the database. Other words are only taken as pronouns if they match a set, so the rest
+    /// of a bio isn't reported.
+    pub unrecognised: Vec<String>,
+}
+
+/// Words that carry no information on their own but show up in declarations all the time.
+const FILLER: &[&str] = &[
+    "pronoun", "pronouns", "prns", "and", "or", "me", "my", "please", "pls", "but", "also", "only",
+    "just", "are", "is", "use",
+];
+
+impl PronounTrie {
+    /// Parse a pronoun declaration as people write them in bios and profile fields, e.g.
+    /// "she/they", "he/him (ask!)", "any pronouns" or "They/Them". Slash-separated words are
+    /// grouped greedily into the longest runs that [PronounTrie::guess] knows about, so
+    /// "she/her/they/them" is two sets and "she/they" is two sets as well. Extra forms after a
+    /// match, like the "hers" in "she/her/hers", narrow that match down instead.
+    pub fn parse_declaration(&self, input: &str) -> Declaration {
+        let input = input.to_lowercase();
+        let words: Vec<&str> = input
+            .split(|c: char| !(c.is_alphanumeric() || c == '/' || c == '\'' || c == '-'))
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        let mut result = Declaration::default();
+
+        for (i, word) in words.iter().enumerate() {
+            let next = words.get(i + 1).copied();
+
+            match *word {
+                "any" | "all" | "any/all" | "all/any" => result.any = true,
+                "ask" => result.ask = true,
+                "no" if next == Some("pronouns") || next == Some("pronoun") => {
+                    result.use_name = true
+                }
+                "name" | "pronoun-free" | "nopronouns" => result.use_name = true,
+                word if FILLER.contains(&word) => {}
+                word => self.parse_group(word, &mut result),
+            }
+        }
+
+        result
+    }
+
+    fn parse_group(&self, word: &str, result: &mut Declaration) {
+        let grouped = word.contains('/');
+        let segments: Vec<&str> = word.split('/').filter(|x| !x.is_empty()).collect();
+
+        let mut i = 0;
+        while i < segments.len() {
+            let found = (i + 1..=segments.len().min(i + 5)).rev().find_map(|j| {
+                let mut key = segments[i..j].iter().map(|x| Some(x.to_string())).collect();
+                let guessed = self.guess(&mut key);
+                (!guessed.is_empty()).then_some((j, guessed))
+            });
+
+            match found {
+                Some((j, mut guessed)) => {
+                    i = j;
+
+                    // People often list forms out of order, like "she/her/hers", so soak up any
+                    // following words that are some other form of the sets we just found.
+                    while let Some(next) = segments.get(i) {
+                        let has_form = |set: &PronounSet| {
+                            Case::ALL.iter().any(|case| set.form(*case) == *next)
+                        };
+                        if !guessed.iter().any(has_form) {
+                            break;
+                        }
+                        guessed.retain(has_form);
+                        i += 1;
+                    }
+
+                    result.sets.push(guessed);
+                }
+                None => {
+                    if grouped {
+                        result.unrecognised.push(segments[i].to_string());
+                    }
+                    i += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The titles of the sets each entry could be, with reflexives to tell the two kinds of
+    /// they/them apart.
+    fn sets(declaration: &Declaration) -> Vec<Vec<String>> {
+        declaration
+            .sets
+            .iter()
+            .map(|sets| {
+                sets.iter()
+                    .map(|set| format!("{}/{}", set.title(), set.reflexive))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn two_sets() {
+        let declaration = crate::test_trie().parse_declaration("she/they");
+        assert_eq!(
+            sets(&declaration),
+            vec![
+                vec!["she/her/herself"],
+                vec!["they/them/themself", "they/them/themselves"],
+            ]
+        );
+        assert!(declaration.unrecognised.is_empty());
+    }
+
+    #[test]
+    fn ask() {
+        let declaration = crate::test_trie().parse_declaration("he/him (ask!)");
+        assert_eq!(sets(&declaration), vec![vec!["he/him/himself"]]);
+        assert!(declaration.ask);
+        assert!(!declaration.any);
+    }
+
+    #[test]
+    fn any() {
+        let declaration = crate::test_trie().parse_declaration("any pronouns");
+        assert!(declaration.any);
+        assert!(declaration.sets.is_empty());
+        assert!(declaration.unrecognised.is_empty());
+    }
+
+    #[test]
+    fn partial_set() {
+        let declaration = crate::test_trie().parse_declaration("xe/xem/xyr");
+        assert_eq!(sets(&declaration), vec![vec!["xe/xem/xemself"]]);
+    }
+
+    #[test]
+    fn capitalised() {
+        let declaration = crate::test_trie().parse_declaration("They/Them");
+        assert_eq!(
+            sets(&declaration),
+            vec![vec!["they/them/themself", "they/them/themselves"]]
+        );
+    }
+
+    #[test]
+    fn no_pronouns() {
+        let declaration = crate::test_trie().parse_declaration("no pronouns, use my name");
+        assert!(declaration.use_name);
+        assert!(declaration.sets.is_empty());
+        assert!(declaration.unrecognised.is_empty());
+    }
+
+    #[test]
+    fn the_rest_of_a_bio_is_ignored() {
+        let declaration =
+            crate::test_trie().parse_declaration("it's me, writing in Rust. she/her, zhe/zher");
+        assert_eq!(sets(&declaration), vec![vec!["she/her/herself"]]);
+        assert_eq!(declaration.unrecognised, vec!["zhe", "zher"]);
+    }
+}
@@ -0,0 +1,170 @@
+use maud::{html, Markup};
+use serde::{Deserialize, Serialize};
+
+use super::{Pack, PronounSet};
+
+/// What someone wants used when referring to them. Most people have a [PronounSet], but some
+/// don't fit into one at all.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Preference {
+    /// A regular pronoun set.
+    Pronouns(PronounSet),
+    /// Any pronouns are fine.
+    Any,
+    /// No pronouns, use this name in every position instead.
+    Name { name: String },
+    /// Ask first.
+    Ask,
+}
+
+impl PronounSet {
+    /// Build the "set" someone who doesn't use pronouns has, with their name in every slot.
+    pub fn name(name: &str) -> Self {
+        let name = name.trim();
+        let possessive = if name.ends_with('s') {
+            format!("{name}'")
+        } else {
+            format!("{name}'s")
+        };
+
+        Self::new(
+            name.to_string(),
+            name.to_string(),
+            possessive.clone(),
+            possessive,
+            name.to_string(),
+        )
+    }
+}
+
+impl Preference {
+    /// Render the page for this preference, with example sentences from the given pack where
+    /// there are any.
+    pub fn page(&self, pack: &Pack) -> Markup {
+        match self {
+            Preference::Pronouns(set) => set.page(pack, None),
+            Preference::Any => html! {
+                p {
+                    "This person is fine with any pronouns. Use whichever ones come to you naturally, and don't worry about switching between them."
+                }
+                p {
+                    "If you're not sure what to pick, "
+                    a href="/they" { "they/them" }
+                    " is always a safe choice. You can also look through "
+                    a href="/pronoun-list" { "all the pronouns in the database" }
+                    " to see how each of them is used."
+                }
+            },
+            Preference::Name { name } => {
+                let set = PronounSet::name(name);
+                html! {
+                    p {
+                        "This person doesn't use pronouns. Use their name, "
+                        em { (name) }
+                        ", everywhere you would normally use a pronoun."
+                    }
+                    (set.forms_table())
+                    p {"Here are some example sentences:"}
+                    ul {
+                        @for example in pack.examples(&set, None) {
+                            li { (example) }
+                        }
+                    }
+                }
+            }
+            Preference::Ask => html! {
+                p {
+                    "This person would like you to ask them which pronouns to use. Until you get the chance, use their name or "
+                    a href="/they" { "they/them" }
+                    "."
+                }
+                p {
+                    "Asking is easy: \"What pronouns do you use?\" works in almost any situation. Share yours at the same time to make it less awkward."
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::PackSource;
+
+    #[test]
+    fn json_is_tagged_with_the_type() {
+        assert_eq!(
+            serde_json::to_value(Preference::Any).unwrap(),
+            json!({"type": "any"})
+        );
+        assert_eq!(
+            serde_json::to_value(Preference::Ask).unwrap(),
+            json!({"type": "ask"})
+        );
+        assert_eq!(
+            serde_json::to_value(Preference::Name {
+                name: "Alex".into()
+            })
+            .unwrap(),
+            json!({"type": "name", "name": "Alex"})
+        );
+
+        let set = PronounSet::new(
+            "xe".into(),
+            "xem".into(),
+            "xyr".into(),
+            "xyrs".into(),
+            "xemself".into(),
+        );
+        let value = serde_json::to_value(Preference::Pronouns(set)).unwrap();
+        assert_eq!(value["type"], "pronouns");
+        assert_eq!(value["nominative"], "xe");
+        assert_eq!(value["agreement"], "singular");
+
+        match serde_json::from_value(value).unwrap() {
+            Preference::Pronouns(set) => assert_eq!(set.title(), "xe/xem"),
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(
+            serde_json::from_value(json!({"type": "name", "name": "Alex"})).unwrap(),
+            Preference::Name { name } if name == "Alex"
+        ));
+    }
+
+    #[test]
+    fn name_possessives() {
+        let sam = PronounSet::name("Sam");
+        assert_eq!(sam.url(), "/Sam/Sam/Sam's/Sam's/Sam");
+        assert!(sam.singular);
+
+        let james = PronounSet::name(" James ");
+        assert_eq!(james.determiner, "James'");
+        assert_eq!(james.possessive, "James'");
+    }
+
+    #[test]
+    fn pages() {
+        let packs: Vec<PackSource> = serde_dhall::from_file("./dhall/examples.dhall")
+            .parse()
+            .expect("dhall/examples.dhall should parse");
+        let pack = Pack::new(packs[0].clone()).unwrap();
+
+        let any = Preference::Any.page(&pack).into_string();
+        assert!(any.contains("any pronouns"));
+
+        let ask = Preference::Ask.page(&pack).into_string();
+        assert!(ask.contains("ask them which pronouns"));
+
+        let name = Preference::Name {
+            name: "James".into(),
+        }
+        .page(&pack)
+        .into_string();
+        assert!(name.contains("<em>James</em>"));
+        assert!(name.contains("James'"));
+        assert!(!name.contains("James's"));
+    }
+}
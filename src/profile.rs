@@ -0,0 +1,301 @@
+use maud::{html, Markup};
+use serde::{Deserialize, Serialize};
+
+use super::{examples::introduce, Example, Pack, PronounSet};
+
+/// How someone with more than one pronoun set wants them used.
+#[derive(Clone, Copy, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Policy {
+    /// Switch between the sets, using each as often as its weight says.
+    #[default]
+    Alternate,
+    /// Use the first set, the others are fine but less preferred.
+    PreferFirst,
+    /// Each set belongs to a different context, like "at work" or "with friends".
+    ContextSpecific,
+}
+
+/// One of the sets in a [PronounProfile].
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ProfileEntry {
+    pub set: PronounSet,
+    /// How often this set should be used relative to the others when alternating.
+    pub weight: f64,
+    /// Where this set is used, for [Policy::ContextSpecific].
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
+/// Everything someone who uses more than one pronoun set wants, such as she/her and they/them.
+#[derive(Clone, Deserialize, Serialize, Default, Debug)]
+pub struct PronounProfile {
+    /// The sets, most preferred first.
+    pub sets: Vec<ProfileEntry>,
+    pub policy: Policy,
+}
+
+impl PronounProfile {
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            sets: Vec::new(),
+            policy,
+        }
+    }
+
+    /// Add a set with the given weight. Sets should be added most preferred first. A weight that
+    /// isn't a positive, finite number counts as 1, since infinite or NaN weights would make the
+    /// running totals of [PronounProfile::schedule] NaN.
+    pub fn push(&mut self, set: PronounSet, weight: f64, context: Option<String>) {
+        let weight = if weight.is_finite() && weight > 0.0 {
+            weight
+        } else {
+            1.0
+        };
+        self.sets.push(ProfileEntry {
+            set,
+            weight,
+            context,
+        });
+    }
+
+    pub fn title(&self) -> String {
+        self.sets
+            .iter()
+            .map(|entry| entry.set.title())
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
+
+    /// The example sentences from a pack mixing the sets according to
+    /// [PronounProfile::schedule], opening with the person's name if there is one.
+    pub fn examples(&self, pack: &Pack, name: Option<&str>) -> Vec<Example> {
+        let mut sets: Vec<Vec<Example>> = self
+            .sets
+            .iter()
+            .map(|entry| pack.examples(&entry.set, None))
+            .collect();
+        let count = sets.first().map_or(0, Vec::len);
+
+        let mut result: Vec<Example> = self
+            .schedule(count)
+            .into_iter()
+            .enumerate()
+            .map(|(i, which)| std::mem::take(&mut sets[which][i]))
+            .collect();
+
+        if let Some(name) = name {
+            introduce(&mut result, name);
+        }
+
+        result
+    }
+
+    /// Pick which set (as an index into `sets`) to use for each of `count` sentences in a row,
+    /// the way a speaker following the policy would. Alternating uses a smooth weighted round
+    /// robin, so two sets with equal weights strictly take turns and a set with twice the weight
+    /// comes up twice as often without clumping together. Preferring the first set uses it for
+    /// everything but the last few sentences, which show each of the others once. Context
+    /// specific profiles always use the first set, their contexts don't mix.
+    pub fn schedule(&self, count: usize) -> Vec<usize> {
+        if self.sets.is_empty() {
+            return Vec::new();
+        }
+
+        match self.policy {
+            Policy::Alternate => {}
+            Policy::PreferFirst => {
+                let others = (self.sets.len() - 1).min(count.saturating_sub(1));
+                let mut result = vec![0; count - others];
+                result.extend(1..=others);
+                return result;
+            }
+            Policy::ContextSpecific => return vec![0; count],
+        }
+
+        let total: f64 = self.sets.iter().map(|entry| entry.weight).sum();
+        let mut current = vec![0.0; self.sets.len()];
+        let mut result = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            for (value, entry) in current.iter_mut().zip(&self.sets) {
+                *value += entry.weight;
+            }
+
+            let (picked, _) = current
+                .iter()
+                .enumerate()
+                .fold((0, f64::MIN), |best, (i, value)| {
+                    if *value > best.1 {
+                        (i, *value)
+                    } else {
+                        best
+                    }
+                });
+
+            current[picked] -= total;
+            result.push(picked);
+        }
+
+        result
+    }
+
+    /// Render the combined page for every set with example sentences from the given pack,
+    /// introducing the person by name in them if one is given.
+    pub fn page(&self, pack: &Pack, name: Option<&str>) -> Markup {
+        html! {
+            @for entry in &self.sets {
+                h2 {
+                    (entry.set.title())
+                    @if let (Policy::ContextSpecific, Some(context)) = (self.policy, &entry.context) {
+                        " (" (context) ")"
+                    }
+                }
+                (entry.set.forms_table())
+                (entry.set.contractions_table())
+                (entry.set.inflection_note())
+            }
+
+            @if let Some(first) = self.sets.first() {
+                @match self.policy {
+                    Policy::Alternate | Policy::PreferFirst => {
+                        @if self.policy == Policy::PreferFirst {
+                            p {
+                                (first.set.title())
+                                " is preferred, but the others are fine too. Here are some example sentences:"
+                            }
+                        } @else {
+                            p {"Here are some example sentences mixing these pronouns:"}
+                        }
+                        ul {
+                            @for example in self.examples(pack, name) {
+                                li { (example) }
+                            }
+                        }
+                    }
+                    Policy::ContextSpecific => {
+                        @for entry in &self.sets {
+                            p {
+                                "Here are some example sentences "
+                                @if let Some(context) = &entry.context {
+                                    (context)
+                                } @else {
+                                    "with " (entry.set.title())
+                                }
+                                ":"
+                            }
+                            ul {
+                                @for example in pack.examples(&entry.set, name) {
+                                    li { (example) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PackSource;
+
+    fn set(nominative: &str) -> PronounSet {
+        PronounSet::new(
+            nominative.to_string(),
+            format!("{nominative}m"),
+            format!("{nominative}r"),
+            format!("{nominative}rs"),
+            format!("{nominative}self"),
+        )
+    }
+
+    fn profile(policy: Policy, weights: &[f64]) -> PronounProfile {
+        let mut profile = PronounProfile::new(policy);
+        for (i, weight) in weights.iter().enumerate() {
+            profile.push(set(&format!("x{i}")), *weight, None);
+        }
+        profile
+    }
+
+    #[test]
+    fn equal_weights_take_turns() {
+        assert_eq!(
+            profile(Policy::Alternate, &[1.0, 1.0]).schedule(5),
+            vec![0, 1, 0, 1, 0]
+        );
+        assert_eq!(
+            profile(Policy::Alternate, &[1.0, 1.0, 1.0]).schedule(6),
+            vec![0, 1, 2, 0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn uneven_weights_dont_clump() {
+        assert_eq!(
+            profile(Policy::Alternate, &[2.0, 1.0]).schedule(6),
+            vec![0, 1, 0, 0, 1, 0]
+        );
+
+        let schedule = profile(Policy::Alternate, &[3.0, 1.0]).schedule(40);
+        assert_eq!(schedule.iter().filter(|&&which| which == 1).count(), 10);
+        assert!(schedule.windows(2).all(|pair| pair != [1, 1]));
+    }
+
+    #[test]
+    fn bad_weights_count_as_one() {
+        for bad in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN, 0.0, -2.0] {
+            let profile = profile(Policy::Alternate, &[bad, 1.0]);
+            assert_eq!(profile.sets[0].weight, 1.0, "{bad}");
+            assert_eq!(profile.schedule(4), vec![0, 1, 0, 1], "{bad}");
+        }
+    }
+
+    #[test]
+    fn prefer_first() {
+        let profile = profile(Policy::PreferFirst, &[1.0, 1.0, 1.0]);
+        assert_eq!(profile.schedule(5), vec![0, 0, 0, 1, 2]);
+        // With too few sentences to show every set, the first one still comes first.
+        assert_eq!(profile.schedule(2), vec![0, 1]);
+        assert_eq!(profile.schedule(1), vec![0]);
+        assert_eq!(profile.schedule(0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn context_specific() {
+        assert_eq!(
+            profile(Policy::ContextSpecific, &[1.0, 5.0]).schedule(3),
+            vec![0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn no_sets() {
+        assert!(profile(Policy::Alternate, &[]).schedule(3).is_empty());
+    }
+
+    #[test]
+    fn examples_follow_the_schedule() {
+        let pack = Pack::new(PackSource {
+            name: "test".into(),
+            description: "test".into(),
+            sentences: vec!["{Nom} left.".into(); 4],
+        })
+        .unwrap();
+
+        let examples: Vec<String> = profile(Policy::Alternate, &[1.0, 1.0])
+            .examples(&pack, None)
+            .iter()
+            .map(Example::to_string)
+            .collect();
+        assert_eq!(
+            examples,
+            vec!["X0 left.", "X1 left.", "X0 left.", "X1 left."]
+        );
+
+        let examples = profile(Policy::Alternate, &[1.0, 1.0]).examples(&pack, Some("Sam"));
+        assert_eq!(examples[0].to_string(), "Sam left.");
+    }
+}
@@ -0,0 +1,572 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use super::{
+    capitalize,
+    verb::{agree, base_form},
+    Case, Number, PronounSet, Verb,
+};
+
+/// Words that can come right after "her" or "his" when it stands on its own, as in "I gave her
+/// the book" or "that one is his too". Anything else after a word that could be a determiner is
+/// taken to be the thing it belongs to.
+const FUNCTION_WORDS: &[&str] = &[
+    "a",
+    "about",
+    "again",
+    "all",
+    "also",
+    "an",
+    "and",
+    "any",
+    "anything",
+    "are",
+    "as",
+    "at",
+    "back",
+    "be",
+    "because",
+    "been",
+    "but",
+    "by",
+    "can",
+    "could",
+    "did",
+    "do",
+    "does",
+    "down",
+    "every",
+    "everything",
+    "for",
+    "from",
+    "had",
+    "has",
+    "have",
+    "her",
+    "here",
+    "him",
+    "his",
+    "i",
+    "if",
+    "in",
+    "into",
+    "is",
+    "it",
+    "me",
+    "might",
+    "must",
+    "my",
+    "nor",
+    "not",
+    "nothing",
+    "now",
+    "of",
+    "off",
+    "on",
+    "or",
+    "our",
+    "out",
+    "over",
+    "should",
+    "so",
+    "some",
+    "something",
+    "than",
+    "that",
+    "the",
+    "their",
+    "them",
+    "then",
+    "there",
+    "these",
+    "this",
+    "those",
+    "through",
+    "to",
+    "too",
+    "up",
+    "us",
+    "very",
+    "was",
+    "we",
+    "were",
+    "when",
+    "while",
+    "will",
+    "with",
+    "would",
+    "yet",
+    "you",
+    "your",
+];
+
+/// Words ending in "s" that often come right after a subject but aren't verbs, so they aren't
+/// taken for one by their spelling.
+const NOT_VERBS: &[&str] = &[
+    "afterwards",
+    "always",
+    "besides",
+    "nevertheless",
+    "perhaps",
+    "sometimes",
+    "thus",
+];
+
+/// Past tenses of common irregular verbs. They are the same for every subject, so they don't
+/// need to agree and aren't worth flagging.
+const PAST_TENSES: &[&str] = &[
+    "ate",
+    "became",
+    "began",
+    "brought",
+    "bought",
+    "came",
+    "felt",
+    "found",
+    "gave",
+    "got",
+    "grew",
+    "heard",
+    "held",
+    "kept",
+    "knew",
+    "left",
+    "lost",
+    "made",
+    "meant",
+    "met",
+    "paid",
+    "put",
+    "ran",
+    "read",
+    "said",
+    "sat",
+    "saw",
+    "sent",
+    "set",
+    "spoke",
+    "stood",
+    "taught",
+    "thought",
+    "told",
+    "took",
+    "understood",
+    "went",
+    "won",
+    "wrote",
+];
+
+/// Words that show the pronoun right before them is the subject, as in "it is" or "it will".
+const SUBJECT_CUES: &[&str] = &[
+    "are", "can", "could", "did", "do", "does", "had", "has", "have", "is", "may", "might", "must",
+    "should", "was", "were", "will", "would",
+];
+
+/// One word that was replaced.
+#[derive(Clone, Serialize, Debug, PartialEq, Eq)]
+pub struct Substitution {
+    /// Where the word starts in the original text, in bytes.
+    pub start: usize,
+    /// Where the word ends in the original text, in bytes.
+    pub end: usize,
+    pub original: String,
+    pub replacement: String,
+    /// The case the word was taken to be in, or None for a verb that was changed to agree with
+    /// the new set.
+    pub case: Option<Case>,
+    /// False if the word could have been more than one form (like "her", which is both the
+    /// accusative and the determiner) and the new set has different words for them, or if it was
+    /// taken to be a regular verb from its spelling alone, so the replacement is a guess that
+    /// should be checked. A word after a plural subject that might be a verb (like "run" in "they
+    /// run") can't be told apart from a past tense or anything else by its spelling, so it is
+    /// flagged with the original word as the replacement instead of being changed.
+    pub certain: bool,
+}
+
+/// Text rewritten from one set to another, along with everything that was replaced.
+#[derive(Clone, Serialize, Debug, PartialEq, Eq)]
+pub struct Rewrite {
+    pub text: String,
+    pub substitutions: Vec<Substitution>,
+}
+
+impl Rewrite {
+    /// The substitutions that were guesses.
+    pub fn uncertain(&self) -> impl Iterator<Item = &Substitution> {
+        self.substitutions.iter().filter(|sub| !sub.certain)
+    }
+}
+
+impl PronounSet {
+    /// Rewrite text that uses this set so that it uses another one instead. Capitalisation and
+    /// punctuation are kept, contractions like "she's" are carried over and the verb right after
+    /// the nominative is changed to agree with the new set, like "she runs" to "they run".
+    pub fn rewrite(&self, text: &str, to: &PronounSet) -> Rewrite {
+        let words = words(text);
+        let adjacent = |a: &Range<usize>, b: &Range<usize>| text[a.end..b.start].trim().is_empty();
+        let mut substitutions = Vec::new();
+
+        let mut i = 0;
+        while i < words.len() {
+            let range = words[i].clone();
+            let word = &text[range.clone()];
+            let before = i
+                .checked_sub(1)
+                .filter(|&j| adjacent(&words[j], &range))
+                .map(|j| &text[words[j].clone()]);
+            let next = words.get(i + 1).filter(|next| adjacent(&range, next));
+            let after = next.map(|next| &text[next.clone()]);
+            i += 1;
+
+            let (replacement, case, certain, subject) =
+                if let Some((case, certain)) = self.case_of(word, before, after, to) {
+                    (
+                        to.form(case).to_string(),
+                        case,
+                        certain,
+                        case == Case::Nominative,
+                    )
+                } else if let Some((replacement, certain)) = self.contract(word, after, to) {
+                    (replacement, Case::Nominative, certain, false)
+                } else {
+                    continue;
+                };
+
+            substitutions.push(Substitution {
+                start: range.start,
+                end: range.end,
+                original: word.to_string(),
+                replacement: match_case(word, &replacement),
+                case: Some(case),
+                certain,
+            });
+
+            if let (true, Some(next)) = (subject, next) {
+                let verb = &text[next.clone()];
+                if let Some((agreeing, verb_certain)) = agree_verb(verb, self.number(), to.number())
+                {
+                    substitutions.push(Substitution {
+                        start: next.start,
+                        end: next.end,
+                        original: verb.to_string(),
+                        replacement: agreeing,
+                        case: None,
+                        certain: certain && verb_certain,
+                    });
+                    i += 1;
+                }
+            }
+        }
+
+        let mut rewritten = String::with_capacity(text.len());
+        let mut at = 0;
+        for sub in &substitutions {
+            rewritten.push_str(&text[at..sub.start]);
+            rewritten.push_str(&sub.replacement);
+            at = sub.end;
+        }
+        rewritten.push_str(&text[at..]);
+
+        Rewrite {
+            text: rewritten,
+            substitutions,
+        }
+    }
+
+    /// Work out which case a word is in if it is one of this set's forms, and whether that is
+    /// certain as far as the other set is concerned.
+    fn case_of(
+        &self,
+        word: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        to: &PronounSet,
+    ) -> Option<(Case, bool)> {
+        let candidates: Vec<Case> = Case::ALL
+            .into_iter()
+            .filter(|&case| self.form(case).eq_ignore_ascii_case(word))
+            .collect();
+
+        let case = match candidates[..] {
+            [] => return None,
+            [case] => case,
+            _ => pick_case(&candidates, before, after),
+        };
+        let certain = candidates
+            .iter()
+            .all(|&other| to.form(other) == to.form(case));
+
+        Some((case, certain))
+    }
+
+    /// Rewrite a contraction of this set's nominative, like "she's", to use the other set.
+    pub(crate) fn contract(
+        &self,
+        word: &str,
+        after: Option<&str>,
+        to: &PronounSet,
+    ) -> Option<(String, bool)> {
+        let (at, apostrophe) = word.char_indices().find(|(_, c)| is_apostrophe(*c))?;
+        let (base, suffix) = (&word[..at], &word[at + apostrophe.len_utf8()..]);
+        if !self.nominative.eq_ignore_ascii_case(base) {
+            return None;
+        }
+
+        let (verb, certain) = match suffix.to_lowercase().as_str() {
+            "re" => ("be", true),
+            "ve" => ("have", true),
+            "ll" => ("will", true),
+            "d" => ("would", true),
+            "s" => {
+                let has = after.is_some_and(|after| {
+                    let after = after.to_lowercase();
+                    after == "got" || after.ends_with("ed") || after.ends_with("en")
+                });
+                // Singular sets contract both verbs to "'s", so the guess only matters for
+                // plural ones.
+                (
+                    if has { "have" } else { "be" },
+                    to.number() == Number::Singular,
+                )
+            }
+            _ => return None,
+        };
+
+        let contracted = to.contraction(verb)?.replace('\'', &apostrophe.to_string());
+        Some((contracted, certain))
+    }
+}
+
+/// Change the word after a nominative so that it agrees with another number, if it is a verb in
+/// the present tense. Irregular verbs like "is" are changed for certain. After a singular subject,
+/// a word that [base_form] can take the "s" off of, like "runs", is taken to be a regular verb
+/// and changed, but the second value is false so the change gets checked. After a plural subject
+/// a regular verb is spelled like any other word, so a word that could be one comes back as it is
+/// and flagged, rather than turning "they went" into "xe wents".
+pub(crate) fn agree_verb(word: &str, from: Number, to: Number) -> Option<(String, bool)> {
+    if let Some(agreeing) = agree(word, from, to) {
+        return Some((match_case(word, agreeing), true));
+    }
+
+    let lower = word.to_lowercase();
+    if from == to
+        || !lower.chars().all(char::is_alphabetic)
+        || [FUNCTION_WORDS, NOT_VERBS, PAST_TENSES]
+            .iter()
+            .any(|list| list.contains(&lower.as_str()))
+        || ["ed", "ing", "ly"]
+            .iter()
+            .any(|suffix| lower.ends_with(suffix))
+    {
+        return None;
+    }
+
+    match from {
+        Number::Singular => {
+            let base = base_form(&lower)?;
+            Some((match_case(word, &Verb(&base).present(to)), false))
+        }
+        Number::Plural if lower.ends_with('s') => None,
+        Number::Plural => Some((word.to_string(), false)),
+    }
+}
+
+/// Pick a case for a word that could be several, like "her" or "his", by looking at the words
+/// right next to it.
+pub(crate) fn pick_case(candidates: &[Case], before: Option<&str>, after: Option<&str>) -> Case {
+    let is_one_of = |word: Option<&str>, list: &[&str]| {
+        word.is_some_and(|w| list.contains(&&*w.to_lowercase()))
+    };
+
+    if candidates.contains(&Case::Determiner)
+        && after.is_some()
+        && !is_one_of(after, FUNCTION_WORDS)
+    {
+        return Case::Determiner;
+    }
+
+    if candidates.contains(&Case::Nominative)
+        && (before.is_none() || is_one_of(after, SUBJECT_CUES))
+    {
+        return Case::Nominative;
+    }
+
+    [
+        Case::Accusative,
+        Case::Possessive,
+        Case::Reflexive,
+        Case::Nominative,
+        Case::Determiner,
+    ]
+    .into_iter()
+    .find(|case| candidates.contains(case))
+    .unwrap_or(candidates[0])
+}
+
+pub(crate) fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+/// The byte ranges of every word in some text. Apostrophes between letters (as in "she's") are
+/// part of the word.
+pub(crate) fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((at, c)) = chars.next() {
+        let inner_apostrophe = is_apostrophe(c)
+            && start.is_some()
+            && chars.peek().is_some_and(|(_, next)| next.is_alphanumeric());
+
+        if c.is_alphanumeric() || inner_apostrophe {
+            start.get_or_insert(at);
+        } else if let Some(begin) = start.take() {
+            words.push(begin..at);
+        }
+    }
+
+    if let Some(begin) = start {
+        words.push(begin..text.len());
+    }
+
+    words
+}
+
+/// Give a replacement the same capitalisation as the word it replaces.
+pub(crate) fn match_case(original: &str, replacement: &str) -> String {
+    let letters = original.chars().filter(|c| c.is_alphabetic()).count();
+
+    if letters > 1 && !original.chars().any(char::is_lowercase) {
+        replacement.to_uppercase()
+    } else if original.starts_with(char::is_uppercase) {
+        capitalize(replacement)
+    } else {
+        replacement.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(forms: &str) -> PronounSet {
+        let forms: Vec<String> = forms.split('/').map(ToString::to_string).collect();
+        PronounSet::new(
+            forms[0].clone(),
+            forms[1].clone(),
+            forms[2].clone(),
+            forms[3].clone(),
+            forms[4].clone(),
+        )
+    }
+
+    fn she() -> PronounSet {
+        set("she/her/her/hers/herself")
+    }
+
+    fn they() -> PronounSet {
+        set("they/them/their/theirs/themselves")
+    }
+
+    fn xe() -> PronounSet {
+        set("xe/xem/xyr/xyrs/xemself")
+    }
+
+    #[test]
+    fn her_before_a_noun_is_the_determiner() {
+        let rewrite = she().rewrite("I found her keys.", &xe());
+        assert_eq!(rewrite.text, "I found xyr keys.");
+        assert_eq!(rewrite.substitutions[0].case, Some(Case::Determiner));
+        assert!(!rewrite.substitutions[0].certain);
+    }
+
+    #[test]
+    fn her_before_a_function_word_is_the_accusative() {
+        assert_eq!(
+            she().rewrite("I gave her the book.", &xe()).text,
+            "I gave xem the book."
+        );
+        assert_eq!(she().rewrite("I saw her.", &xe()).text, "I saw xem.");
+    }
+
+    #[test]
+    fn his_on_its_own_is_the_possessive() {
+        let he = set("he/him/his/his/himself");
+        assert_eq!(
+            he.rewrite("That one is his.", &xe()).text,
+            "That one is xyrs."
+        );
+        assert_eq!(
+            he.rewrite("That is his bag.", &xe()).text,
+            "That is xyr bag."
+        );
+    }
+
+    #[test]
+    fn irregular_verbs_agree_for_certain() {
+        let rewrite = she().rewrite("She is here and she has time.", &they());
+        assert_eq!(rewrite.text, "They are here and they have time.");
+        assert_eq!(rewrite.uncertain().count(), 0);
+    }
+
+    #[test]
+    fn regular_verbs_agree_but_are_flagged() {
+        let rewrite = she().rewrite("She runs home. She watches TV.", &they());
+        assert_eq!(rewrite.text, "They run home. They watch TV.");
+        let uncertain: Vec<&str> = rewrite
+            .uncertain()
+            .map(|sub| sub.original.as_str())
+            .collect();
+        assert_eq!(uncertain, ["runs", "watches"]);
+
+        // After a plural subject the verb is only flagged, since it could be anything.
+        let rewrite = they().rewrite("They carry it.", &xe());
+        assert_eq!(rewrite.text, "Xe carry it.");
+        let uncertain: Vec<(&str, &str)> = rewrite
+            .uncertain()
+            .map(|sub| (sub.original.as_str(), sub.replacement.as_str()))
+            .collect();
+        assert_eq!(uncertain, [("carry", "carry")]);
+    }
+
+    #[test]
+    fn past_tenses_and_modals_are_left_alone() {
+        for (text, expected) in [
+            ("They went home.", "Xe went home."),
+            ("They said so.", "Xe said so."),
+            ("They could help.", "Xe could help."),
+            ("They must leave.", "Xe must leave."),
+            ("They were late.", "Xe was late."),
+        ] {
+            let rewrite = they().rewrite(text, &xe());
+            assert_eq!(rewrite.text, expected);
+            assert_eq!(rewrite.uncertain().count(), 0, "{text}");
+        }
+
+        for (text, expected) in [
+            ("She went home.", "They went home."),
+            ("She said so.", "They said so."),
+            ("She could help.", "They could help."),
+            ("She must leave.", "They must leave."),
+        ] {
+            let rewrite = she().rewrite(text, &they());
+            assert_eq!(rewrite.text, expected);
+            assert_eq!(rewrite.uncertain().count(), 0, "{text}");
+        }
+    }
+
+    #[test]
+    fn other_words_after_the_nominative_are_left_alone() {
+        for text in ["She will go.", "She quickly left.", "She walked home."] {
+            assert_eq!(
+                she().rewrite(text, &they()).uncertain().count(),
+                0,
+                "{text}"
+            );
+        }
+        assert_eq!(she().rewrite("She's here.", &they()).text, "They're here.");
+        assert_eq!(they().rewrite("They also ran.", &xe()).text, "Xe also ran.");
+    }
+}
@@ -0,0 +1,217 @@
+use std::str::FromStr;
+
+use xe_pronouns_syntax::{tokenize, Placeholder, Token};
+
+use super::{capitalize, examples::Part, Case, PronounSet};
+
+/// Why a template couldn't be parsed.
+pub use xe_pronouns_syntax::Error as TemplateError;
+
+/// One piece of a parsed template. The flags say whether the output should be capitalised.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    /// A pronoun form.
+    Form(Case, bool),
+    /// The nominative contracted with a verb, like "they're".
+    Contraction(String, bool),
+    /// A verb in its bare infinitive, conjugated to agree with the set.
+    Verb(String, bool),
+    /// Two alternatives, the first for sets that refer to one person and the second for sets
+    /// that refer to a group.
+    Number(String, String, bool),
+}
+
+/// A string with placeholders for pronoun forms, parsed once so that it can be rendered for any
+/// [PronounSet].
+///
+/// Placeholders are written in braces:
+///
+/// - `{nom}`, `{acc}`, `{det}`, `{pos}` and `{ref}` (or `{nominative}`, `{accusative}`,
+///   `{determiner}`, `{possessive}` and `{reflexive}`) insert that form.
+/// - `{nom+be}`, `{nom+have}`, `{nom+will}`, `{nom+would}` and `{nom+had}` insert the nominative
+///   contracted with that verb, like "they're" or "xe's".
+/// - `{verb:go}` conjugates a verb to agree with the set, giving "goes" or "go".
+/// - `{to|between}` picks the first word for sets that refer to one person and the second for
+///   sets that refer to a group.
+///
+/// Capitalising a placeholder (`{Nom}`) capitalises what it inserts. Placeholders at the start
+/// of a sentence are capitalised either way. Write `{{` and `}}` for literal braces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let pieces = tokenize(source)?
+            .into_iter()
+            .map(|token| {
+                let capitalised = token.capitalised();
+                Ok(match token {
+                    Token::Text(text) => Piece::Text(text),
+                    Token::Placeholder { name, .. } => match Placeholder::parse(name)? {
+                        Placeholder::Form(case) => Piece::Form(case.into(), capitalised),
+                        Placeholder::Contraction(verb) => {
+                            Piece::Contraction(verb.to_string(), capitalised)
+                        }
+                        Placeholder::Verb(verb) => Piece::Verb(verb.to_string(), capitalised),
+                        Placeholder::Number(one, many) => {
+                            Piece::Number(one.to_string(), many.to_string(), capitalised)
+                        }
+                    },
+                })
+            })
+            .collect::<Result<_, TemplateError>>()?;
+
+        Ok(Self { pieces })
+    }
+
+    /// Fill in the template for a set, keeping track of which parts are pronouns.
+    pub(crate) fn parts(&self, set: &PronounSet) -> Vec<Part> {
+        let cap = |text: String, capitalised: bool| {
+            if capitalised {
+                capitalize(&text)
+            } else {
+                text
+            }
+        };
+
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => Part::Text(text.clone()),
+                Piece::Form(case, capitalised) => {
+                    Part::Form(*case, cap(set.form(*case).to_string(), *capitalised))
+                }
+                // This is text and not a form so that introducing someone by name doesn't
+                // swallow the contracted verb.
+                Piece::Contraction(verb, capitalised) => {
+                    Part::Text(cap(set.contraction(verb).unwrap_or_default(), *capitalised))
+                }
+                Piece::Verb(verb, capitalised) => {
+                    Part::Verb(verb.clone(), cap(set.conjugate(verb), *capitalised))
+                }
+                Piece::Number(one, many, capitalised) => Part::Text(cap(
+                    if set.singular { one } else { many }.clone(),
+                    *capitalised,
+                )),
+            })
+            .collect()
+    }
+
+    /// Render the template for a set.
+    pub fn render(&self, set: &PronounSet) -> String {
+        self.parts(set)
+            .into_iter()
+            .map(|part| match part {
+                Part::Text(text) | Part::Form(_, text) | Part::Name(text) | Part::Verb(_, text) => {
+                    text
+                }
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(key: &[&str]) -> PronounSet {
+        let trie = crate::test_trie();
+        let mut key = key.iter().map(|form| Some(form.to_string())).collect();
+        trie.guess(&mut key).remove(0)
+    }
+
+    fn render(source: &str, key: &[&str]) -> String {
+        Template::parse(source).unwrap().render(&set(key))
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (source, error) in [
+            (
+                "{nominitive}",
+                TemplateError::UnknownPlaceholder("nominitive".into()),
+            ),
+            ("{}", TemplateError::UnknownPlaceholder("".into())),
+            (
+                "{nom+go}",
+                TemplateError::UnknownContraction("nom+go".into()),
+            ),
+            (
+                "{acc+be}",
+                TemplateError::UnknownContraction("acc+be".into()),
+            ),
+            ("{verb:}", TemplateError::Incomplete("verb:".into())),
+            (
+                "{verb:go home}",
+                TemplateError::Incomplete("verb:go home".into()),
+            ),
+            ("{|}", TemplateError::Incomplete("|".into())),
+            ("{a|}", TemplateError::Incomplete("a|".into())),
+            ("{|b}", TemplateError::Incomplete("|b".into())),
+            ("hi {nom", TemplateError::Unclosed(3)),
+        ] {
+            assert_eq!(Template::parse(source), Err(error), "{source}");
+        }
+    }
+
+    #[test]
+    fn braces() {
+        assert_eq!(render("{{nom}} is {nom}}", &["xe"]), "{nom} is xe}");
+    }
+
+    #[test]
+    fn capitalisation() {
+        assert_eq!(
+            render("{nom} left. Then {nom} came back! {Acc}? ok", &["she"]),
+            "She left. Then she came back! Her? ok"
+        );
+        assert_eq!(render("a.b {nom}", &["she"]), "a.b she");
+        assert_eq!(render("{to|between} {ref}", &["they"]), "To themself");
+    }
+
+    #[test]
+    fn contractions() {
+        assert_eq!(render("{nom+be} here", &["they"]), "They're here");
+        assert_eq!(render("{nom+have} been", &["xe"]), "Xe's been");
+        assert_eq!(render("so {nom+will} go", &["she"]), "so she'll go");
+        assert_eq!(render("so {nom+had} gone", &["they"]), "so they'd gone");
+    }
+
+    #[test]
+    fn every_contraction_contracts() {
+        for verb in xe_pronouns_syntax::CONTRACTIONS {
+            assert!(
+                crate::Verb(verb).contraction(Default::default()).is_some(),
+                "{verb}"
+            );
+        }
+    }
+
+    #[test]
+    fn agreement() {
+        for (key, expected) in [
+            (&["she"][..], "She goes and carries it, she is fine."),
+            (&["they"][..], "They go and carry it, they are fine."),
+            (&["xe"][..], "Xe goes and carries it, xe is fine."),
+        ] {
+            assert_eq!(
+                render(
+                    "{nom} {verb:go} and {verb:carry} it, {nom} {verb:be} fine.",
+                    key
+                ),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use maud::{html, Markup, Render};
+use serde::{Deserialize, Serialize};
+
+use super::clean_name;
+
+/// The words to use for someone besides their pronouns: how to address them and what to call them
+/// in relation to other people.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct Terms {
+    /// Like "Mx." or "Ms.".
+    pub honorific: String,
+    pub parent: String,
+    pub sibling: String,
+    pub partner: String,
+    pub child: String,
+    /// A word for them as a person, like "person" or "woman".
+    pub person: String,
+}
+
+/// The terms someone has asked for. Anything left out falls back to the defaults.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+pub struct TermPreferences {
+    pub honorific: Option<String>,
+    pub parent: Option<String>,
+    pub sibling: Option<String>,
+    pub partner: Option<String>,
+    pub child: Option<String>,
+    pub person: Option<String>,
+}
+
+impl TermPreferences {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Every term that was asked for, as a label and the cleaned up word. Terms that clean up to
+    /// nothing are left out.
+    fn rows(&self) -> Vec<(&'static str, String)> {
+        [
+            ("Honorific", &self.honorific),
+            ("Parent", &self.parent),
+            ("Sibling", &self.sibling),
+            ("Partner", &self.partner),
+            ("Child", &self.child),
+            ("Person", &self.person),
+        ]
+        .into_iter()
+        .filter_map(|(label, term)| Some((label, clean_name(term.as_deref()?)?)))
+        .collect()
+    }
+}
+
+impl Terms {
+    /// Apply someone's preferences on top of these terms. Preferences come from user input, so
+    /// they are cleaned up the same way names are.
+    pub fn with(&self, preferences: &TermPreferences) -> Self {
+        let pick = |preference: &Option<String>, default: &String| {
+            preference
+                .as_deref()
+                .and_then(clean_name)
+                .unwrap_or_else(|| default.clone())
+        };
+
+        Self {
+            honorific: pick(&preferences.honorific, &self.honorific),
+            parent: pick(&preferences.parent, &self.parent),
+            sibling: pick(&preferences.sibling, &self.sibling),
+            partner: pick(&preferences.partner, &self.partner),
+            child: pick(&preferences.child, &self.child),
+            person: pick(&preferences.person, &self.person),
+        }
+    }
+}
+
+/// Only the terms someone asked for, so a page doesn't claim they want "Mx." when they never
+/// said. Renders nothing if they didn't ask for any.
+impl Render for TermPreferences {
+    fn render(&self) -> Markup {
+        table(&self.rows())
+    }
+}
+
+fn table(rows: &[(&str, String)]) -> Markup {
+    html! {
+        @if !rows.is_empty() {
+            p {"When talking about this person, use these words:"}
+            table {
+                @for (label, term) in rows {
+                    tr {
+                        th { (label) }
+                        td { (term) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_asked_for_terms_are_rendered() {
+        assert_eq!(TermPreferences::default().render().into_string(), "");
+
+        let preferences = TermPreferences {
+            honorific: Some(" Ms. ".to_string()),
+            sibling: Some("sister".to_string()),
+            person: Some("\u{7}".to_string()),
+            ..Default::default()
+        };
+        let html = preferences.render().into_string();
+        assert!(html.contains("<th>Honorific</th><td>Ms.</td>"));
+        assert!(html.contains("<th>Sibling</th><td>sister</td>"));
+        assert!(!html.contains("Parent"));
+        assert!(!html.contains("Person"));
+    }
+
+    #[test]
+    fn preferences_override_defaults() {
+        let defaults: Terms = serde_dhall::from_file("./dhall/terms.dhall")
+            .parse()
+            .expect("dhall/terms.dhall should parse");
+        let terms = defaults.with(&TermPreferences {
+            sibling: Some("sister".to_string()),
+            parent: Some("".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(terms.honorific, "Mx.");
+        assert_eq!(terms.sibling, "sister");
+        assert_eq!(terms.parent, "parent");
+        assert_eq!(defaults.with(&TermPreferences::default()), defaults);
+    }
+}
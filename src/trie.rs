@@ -1,9 +1,12 @@
-use super::PronounSet;
+use std::cmp::Ordering;
 
-/// An intermediate pronoun is either complete and stores an ordered Vec of nominative,
-/// accusative, &c strings, and a boolean plurality indicator. If it is incomplete, the plurality
-/// indicator is None and Vec<String> has fewer than 5 elements.
-type IntPron = (Vec<String>, Option<bool>);
+use serde::Serialize;
+
+use super::{
+    forms::{FormIndex, FormMatch},
+    infer::Rules,
+    normalize, url_to_trie_query, Case, PronounSet,
+};
 
 #[derive(Debug)]
 pub struct PronounTrie {
@@ -12,58 +15,190 @@ pub struct PronounTrie {
     right: Option<Box<PronounTrie>>,
     next:  Option<Box<PronounTrie>>,
 
-    /// If this node terminates a PronounSet, store whether or not the pronoun is singular.
-    singular: Option<bool>,
+    /// If this node terminates a PronounSet, store the whole set so that everything that isn't
+    /// part of the key (like its grammatical number) survives the round trip.
+    set: Option<PronounSet>,
+
+    /// Only the root has this, to look sets up by any of their forms.
+    forms: Option<Box<FormIndex>>,
+
+    /// Only the root has this, to fill in missing forms with [PronounTrie::infer].
+    rules: Option<Box<Rules>>,
+}
+
+/// A set that is close to what was looked up, for "did you mean" suggestions.
+#[derive(Clone, Serialize, Debug)]
+pub struct FuzzyMatch {
+    pub set: PronounSet,
+    /// How many single-character edits (insertions, deletions, substitutions or swapping two
+    /// neighbouring letters) it takes to get from the key to this set, over all of its forms.
+    pub distance: usize,
+}
+
+/// A set that starts with what has been typed so far, for autocompletion.
+#[derive(Clone, Serialize, Debug)]
+pub struct Completion {
+    pub set: PronounSet,
+    pub url: String,
 }
 
 impl PronounTrie {
-    /// Build a trie out of a vector of pronouns.
+    /// Build a balanced trie out of a vector of pronouns. At every level the sets are sorted and
+    /// the median form goes at the top, so lookups take logarithmic time no matter what order the
+    /// input is in. If two sets have the same forms, the later one wins.
     pub fn build(pronouns: Vec<PronounSet>) -> Self {
-        let mut base: Option<Box<PronounTrie>> = None;
+        let mut keyed: Vec<(Vec<String>, PronounSet)> = pronouns
+            .into_iter()
+            .map(|pronoun| (key_of(&pronoun), pronoun))
+            .collect();
 
-        for pronoun in pronouns {
-            let key = vec![
-                pronoun.nominative,
-                pronoun.accusative,
-                pronoun.determiner,
-                pronoun.possessive,
-                pronoun.reflexive,
-            ];
+        // The sort is stable, so keeping the last of each run keeps the later set.
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        keyed.reverse();
+        keyed.dedup_by(|a, b| a.0 == b.0);
+        keyed.reverse();
 
-            Self::insert(&mut base, key, pronoun.singular);
+        let forms = FormIndex::build(keyed.iter().map(|(_, set)| set));
+        let mut rules = Rules::default();
+        for (_, set) in &keyed {
+            rules.learn(set);
         }
 
-        *base.expect("non-empty input list")
+        let mut root = Self::build_level(keyed, 0).expect("non-empty input list");
+        root.forms = Some(Box::new(forms));
+        root.rules = Some(Box::new(rules));
+
+        *root
+    }
+
+    /// Add a set to the trie, replacing any set with the same forms. If this makes one of the
+    /// levels it passes through much deeper than it needs to be, that level is rebuilt balanced.
+    pub fn insert(&mut self, set: PronounSet) {
+        let key = key_of(&set);
+
+        let mut rules = self.rules.take().unwrap_or_default();
+        let mut replaced = key.iter().cloned().map(Some).collect();
+        if let Some(old) = self.guess(&mut replaced).pop() {
+            rules.forget(&old);
+        }
+        rules.learn(&set);
+
+        // Rebalancing the top level can move the root node, so hold on to the indexes until it's
+        // done.
+        let mut forms = self.forms.take().unwrap_or_default();
+        forms.insert(&set);
+        self.insert_level(&key, set);
+        self.forms = Some(forms);
+        self.rules = Some(rules);
+    }
+
+    /// The rules [PronounTrie::infer] fills in missing forms with.
+    pub(crate) fn rules(&self) -> Option<&Rules> {
+        self.rules.as_deref()
+    }
+
+    /// Every set that has this form in any case, with the case it is in. Sets come in the order
+    /// of [Case::ALL] and then alphabetically, so "hir" finds the accusatives before the
+    /// determiners.
+    pub fn by_form(&self, form: &str) -> Vec<FormMatch> {
+        Case::ALL
+            .iter()
+            .flat_map(|&case| {
+                self.with_form(case, form).iter().map(move |set| FormMatch {
+                    set: set.clone(),
+                    case,
+                })
+            })
+            .collect()
+    }
+
+    /// Every set that has this form in one case.
+    pub fn with_form(&self, case: Case, form: &str) -> &[PronounSet] {
+        self.forms
+            .as_ref()
+            .map(|forms| forms.get(case, form))
+            .unwrap_or_default()
     }
 
     /// Take a vector of optional strings and return a list of matching pronouns. If None is passed
     /// as one of the key element,s it may match any string.
     pub fn guess(&self, key: &mut Vec<Option<String>>) -> Vec<PronounSet> {
-        // Expand wildcards to fill length 5. Not the prettiest code.
-        let expansion = 5 - key.len();
-        for (i, word) in key.iter().enumerate() {
-            if word.is_none() {
-                for _ in 0..expansion {
-                    key.insert(i, None);
-                }
-                break;
-            }
+        expand_wildcards(key);
+        normalize_key(key);
+        self.guess_sets(key)
+    }
+
+    /// Find the set a URL fragment like "she/her" refers to: the last set [PronounTrie::guess]
+    /// finds, or the set spelled out by all five forms if none is. Missing forms are never
+    /// inferred, so a typo like "shee/her" finds nothing instead of a set nobody uses. Everything
+    /// that takes a set from a user goes through this, so a set means the same thing everywhere.
+    pub fn resolve(&self, pronoun: &str) -> Option<PronounSet> {
+        if let Some(set) = self.guess(&mut url_to_trie_query(pronoun)).pop() {
+            return Some(set);
+        }
+
+        match pronoun.split('/').collect::<Vec<&str>>()[..] {
+            [nominative, accusative, determiner, possessive, reflexive] => Some(PronounSet::new(
+                nominative.to_string(),
+                accusative.to_string(),
+                determiner.to_string(),
+                possessive.to_string(),
+                reflexive.to_string(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Find every set within `max_distance` edits of a key, closest first. The key is given like
+    /// the one to [PronounTrie::guess], and None still matches any form for free, so `thye/them`
+    /// finds they/them at a distance of 1. A key without any forms finds nothing, since there is
+    /// nothing in it to have a typo.
+    pub fn fuzzy(&self, key: &[Option<String>], max_distance: usize) -> Vec<FuzzyMatch> {
+        if key.iter().all(Option::is_none) {
+            return vec![];
         }
 
-        let mut strings = self.guess_strings(key);
+        let mut key = key.to_vec();
+        expand_wildcards(&mut key);
+        normalize_key(&mut key);
 
-        strings.drain(..).filter_map(|(x, singular)| if x.len() == 5 {
-            Some(PronounSet {
-                nominative: x[0].clone(),
-                accusative: x[1].clone(),
-                determiner: x[2].clone(),
-                possessive: x[3].clone(),
-                reflexive:  x[4].clone(),
-                singular:   singular.unwrap(),
+        let mut result = Vec::new();
+        self.fuzzy_sets(&key, max_distance, 0, &mut result);
+
+        // The sort is stable, so matches at the same distance stay in alphabetical order.
+        result.sort_by_key(|found| found.distance);
+        result
+    }
+
+    /// Complete a partly typed set like `xe/x`. Every form but the last has to match exactly, and
+    /// the last one is a prefix. Sets where the last form is already complete come first, then
+    /// ones with shorter forms, so typing `he` suggests he/him before hey/hem.
+    pub fn complete(&self, typed: &[String]) -> Vec<Completion> {
+        let typed: Vec<String> = typed.iter().map(|form| normalize(form)).collect();
+        let Some(prefix) = typed.last() else {
+            return vec![];
+        };
+        if typed.len() > Case::ALL.len() || typed.iter().all(String::is_empty) {
+            return vec![];
+        }
+
+        let mut found = Vec::new();
+        self.complete_sets(&typed, &mut found);
+
+        let case = Case::ALL[typed.len() - 1];
+        // The sort is stable, so ties stay in alphabetical order.
+        found.sort_by_key(|set| {
+            let form = normalize(set.form(case));
+            (form != *prefix, form.chars().count())
+        });
+
+        found
+            .into_iter()
+            .map(|set| Completion {
+                url: set.url(),
+                set,
             })
-        } else {
-            None
-        }).collect()
+            .collect()
     }
 
     /// Get all strings in the set.
@@ -71,53 +206,157 @@ impl PronounTrie {
         self.guess(&mut Vec::new())
     }
 
+    /// The most nodes a lookup has to visit, counting the root. An exact lookup of a set visits
+    /// at most this many.
+    pub fn max_depth(&self) -> usize {
+        1 + [&self.left, &self.right, &self.next]
+            .into_iter()
+            .flatten()
+            .map(|child| child.max_depth())
+            .max()
+            .unwrap_or(0)
+    }
+
     fn new(inner: String) -> Self {
         Self {
             inner,
             left: None,
             right: None,
             next: None,
-            singular: None,
+            set: None,
+            forms: None,
+            rules: None,
         }
     }
 
-    fn insert(s: &mut Option<Box<Self>>, mut key: Vec<String>, singular: bool) {
-        match s {
-            None => {
-                let car = key[0].clone();
-                key.remove(0);
-                let cons = key;
+    /// Build the level of the trie for the form at `depth` out of sorted sets that all share the
+    /// forms before it.
+    fn build_level(keyed: Vec<(Vec<String>, PronounSet)>, depth: usize) -> Option<Box<Self>> {
+        let mut nodes: Vec<Self> = Vec::new();
+        let mut group: Vec<(Vec<String>, PronounSet)> = Vec::new();
 
-                let mut child = Self::new(car);
+        let mut finish = |group: Vec<(Vec<String>, PronounSet)>| {
+            let mut node = Self::new(group[0].0[depth].clone());
+            if depth + 1 < group[0].0.len() {
+                node.next = Self::build_level(group, depth + 1);
+            } else {
+                node.set = group.into_iter().last().map(|(_, set)| set);
+            }
+            nodes.push(node);
+        };
 
-                if !cons.is_empty() {
-                    Self::insert(&mut child.next, cons, singular);
-                } else {
-                    child.singular = Some(singular);
-                }
+        for item in keyed {
+            if !group.is_empty() && group[0].0[depth] != item.0[depth] {
+                finish(std::mem::take(&mut group));
+            }
+            group.push(item);
+        }
+        if !group.is_empty() {
+            finish(group);
+        }
+
+        Self::balance(nodes)
+    }
+
+    /// Arrange nodes that are sorted by their form into a balanced binary tree, and return its
+    /// root. The nodes must not have left or right children.
+    fn balance(mut nodes: Vec<Self>) -> Option<Box<Self>> {
+        if nodes.is_empty() {
+            return None;
+        }
+
+        let right = nodes.split_off(nodes.len() / 2 + 1);
+        let mut root = nodes.pop()?;
+        root.left = Self::balance(nodes);
+        root.right = Self::balance(right);
+
+        Some(Box::new(root))
+    }
+
+    /// Insert into the level this node is the root of, then rebalance it if it got too deep.
+    fn insert_level(&mut self, key: &[String], set: PronounSet) {
+        self.insert_here(key, set);
 
-                s.replace(Box::new(child));
-            },
-            Some(s) => {
-                let car = &key[0];
-
-                let s = if car < &s.inner {
-                    &mut s.left
-                } else if car > &s.inner {
-                    &mut s.right
-                } else {
-                    // We found where to insert, advance the key.
-                    key.remove(0);
-                    &mut s.next
-                };
-
-                Self::insert(s, key, singular);
-            },
+        // A balanced level is floor(log2(size)) + 1 nodes deep. Allowing twice that means a level
+        // is only rebuilt after many unlucky inserts, so rebuilding is cheap on average.
+        let size = self.level_size();
+        let balanced = (usize::BITS - size.leading_zeros()) as usize;
+        if self.level_depth() > 2 * balanced {
+            self.rebalance_level();
+        }
+    }
+
+    fn insert_here(&mut self, key: &[String], set: PronounSet) {
+        let child = match key[0].cmp(&self.inner) {
+            Ordering::Less => &mut self.left,
+            Ordering::Greater => &mut self.right,
+            Ordering::Equal => {
+                match &mut self.next {
+                    Some(next) => next.insert_level(&key[1..], set),
+                    None if key.len() == 1 => self.set = Some(set),
+                    None => self.next = Some(Box::new(Self::chain(&key[1..], set))),
+                }
+                return;
+            }
         };
+
+        match child {
+            Some(child) => child.insert_here(key, set),
+            None => *child = Some(Box::new(Self::chain(key, set))),
+        }
     }
 
-    fn guess_strings(&self, key: &mut Vec<Option<String>>) -> Vec<IntPron> {
-        let car = key.get(0).and_then(|x| x.as_ref());
+    /// A straight line of nodes spelling out the key, ending in the set.
+    fn chain(key: &[String], set: PronounSet) -> Self {
+        let mut node = Self::new(key[0].clone());
+        if key.len() == 1 {
+            node.set = Some(set);
+        } else {
+            node.next = Some(Box::new(Self::chain(&key[1..], set)));
+        }
+        node
+    }
+
+    /// How many nodes are in the level this node is the root of, not counting the levels below.
+    fn level_size(&self) -> usize {
+        1 + [&self.left, &self.right]
+            .into_iter()
+            .flatten()
+            .map(|child| child.level_size())
+            .sum::<usize>()
+    }
+
+    /// How deep the level this node is the root of is, not counting the levels below.
+    fn level_depth(&self) -> usize {
+        1 + [&self.left, &self.right]
+            .into_iter()
+            .flatten()
+            .map(|child| child.level_depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn rebalance_level(&mut self) {
+        let root = std::mem::replace(self, Self::new(String::new()));
+        let mut nodes = Vec::new();
+        Self::flatten(root, &mut nodes);
+        *self = *Self::balance(nodes).expect("a level has at least one node");
+    }
+
+    /// Take the nodes of a level out in order, detaching their left and right children.
+    fn flatten(mut node: Self, nodes: &mut Vec<Self>) {
+        let right = node.right.take();
+        if let Some(left) = node.left.take() {
+            Self::flatten(*left, nodes);
+        }
+        nodes.push(node);
+        if let Some(right) = right {
+            Self::flatten(*right, nodes);
+        }
+    }
+
+    fn guess_sets(&self, key: &[Option<String>]) -> Vec<PronounSet> {
+        let car = key.first().and_then(|x| x.as_ref());
 
         let wildcard = car.is_none();
 
@@ -129,36 +368,373 @@ impl PronounTrie {
 
         if search_left {
             if let Some(left) = self.left.as_ref() {
-                result.extend(left.guess_strings(key));
+                result.extend(left.guess_sets(key));
             }
         }
 
         if search_down {
             if let Some(next) = self.next.as_ref() {
-                if !key.is_empty() {
-                    key.remove(0);
-                }
-
-                let mut basket = next.guess_strings(key);
-
-                let basket = basket.drain(..).map(|(x, singular)| {
-                    let mut y = vec![self.inner.clone()];
-                    y.extend(x);
-                    (y, singular)
-                });
-
-                result.extend(basket.collect::<Vec<(Vec<String>, Option<bool>)>>());
+                // Each branch gets its own view of the key, so searching one can't change what
+                // the others look for.
+                result.extend(next.guess_sets(key.get(1..).unwrap_or_default()));
             } else {
-                result.extend(vec![(vec![self.inner.clone()], self.singular)]);
+                result.extend(self.set.clone());
             }
         }
 
         if search_right {
             if let Some(right) = self.right.as_ref() {
-                result.extend(right.guess_strings(key));
+                result.extend(right.guess_sets(key));
             }
         }
 
         result
     }
+
+    /// Collect every set below this level that matches the typed forms, with the last one as a
+    /// prefix. Forms with the same prefix sit next to each other in a level, so only that part of
+    /// it is searched.
+    fn complete_sets(&self, typed: &[String], result: &mut Vec<PronounSet>) {
+        let word = &typed[0];
+        let last = typed.len() == 1;
+        let matches = if last {
+            self.inner.starts_with(word.as_str())
+        } else {
+            self.inner == *word
+        };
+
+        if word < &self.inner {
+            if let Some(left) = self.left.as_ref() {
+                left.complete_sets(typed, result);
+            }
+        }
+
+        if matches {
+            match (&self.next, last) {
+                (Some(next), true) => result.extend(next.gather()),
+                (Some(next), false) => next.complete_sets(&typed[1..], result),
+                (None, _) => result.extend(self.set.clone()),
+            }
+        }
+
+        if word > &self.inner || (last && matches) {
+            if let Some(right) = self.right.as_ref() {
+                right.complete_sets(typed, result);
+            }
+        }
+    }
+
+    /// Collect every set below this level within `budget` more edits. Edit distance doesn't follow
+    /// the order of the tree, so every node in a level is checked, but a branch is only followed
+    /// down while it is still within budget.
+    fn fuzzy_sets(
+        &self,
+        key: &[Option<String>],
+        budget: usize,
+        spent: usize,
+        result: &mut Vec<FuzzyMatch>,
+    ) {
+        for child in [&self.left, &self.right].into_iter().flatten() {
+            child.fuzzy_sets(key, budget, spent, result);
+        }
+
+        let cost = match key.first() {
+            Some(Some(word)) => edit_distance(word, &self.inner, budget),
+            _ => Some(0),
+        };
+        let Some(cost) = cost else {
+            return;
+        };
+
+        match (&self.next, &self.set) {
+            (Some(next), _) => next.fuzzy_sets(
+                key.get(1..).unwrap_or_default(),
+                budget - cost,
+                spent + cost,
+                result,
+            ),
+            (None, Some(set)) => result.push(FuzzyMatch {
+                set: set.clone(),
+                distance: spent + cost,
+            }),
+            (None, None) => {}
+        }
+    }
+}
+
+/// The optimal string alignment distance between two words: Levenshtein distance, but swapping
+/// two neighbouring letters only counts as one edit. Returns None as soon as it is sure to be more
+/// than `max`.
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    // Only the last two rows of the table are needed.
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+
+        for j in 1..=b.len() {
+            let substitution = previous[j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            current[j] = substitution.min(previous[j] + 1).min(current[j - 1] + 1);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+
+        if current.iter().all(|&cost| cost > max) {
+            return None;
+        }
+
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Some(previous[b.len()]).filter(|&distance| distance <= max)
+}
+
+/// The forms of a set in the order the trie stores them, normalised the same way as lookups.
+pub(crate) fn key_of(set: &PronounSet) -> Vec<String> {
+    Case::ALL
+        .iter()
+        .map(|&case| normalize(set.form(case)))
+        .collect()
+}
+
+/// Normalise every form in a key so it can be compared with the ones in the trie.
+fn normalize_key(key: &mut [Option<String>]) {
+    for form in key.iter_mut().flatten() {
+        *form = normalize(form);
+    }
+}
+
+/// Expand the first wildcard in a key so that the key has length 5. Not the prettiest code.
+pub(crate) fn expand_wildcards(key: &mut Vec<Option<String>>) {
+    let expansion = 5usize.saturating_sub(key.len());
+    for (i, word) in key.iter().enumerate() {
+        if word.is_none() {
+            for _ in 0..expansion {
+                key.insert(i, None);
+            }
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A made up set whose forms all start with `nominative`.
+    fn set(nominative: &str) -> PronounSet {
+        PronounSet::new(
+            nominative.to_string(),
+            format!("{nominative}m"),
+            format!("{nominative}r"),
+            format!("{nominative}rs"),
+            format!("{nominative}self"),
+        )
+    }
+
+    fn titles(sets: &[PronounSet]) -> Vec<String> {
+        sets.iter().map(PronounSet::title).collect()
+    }
+
+    /// Every set found by looking up each nominative alone.
+    fn by_nominative(trie: &PronounTrie, sets: &[PronounSet]) -> Vec<Vec<String>> {
+        sets.iter()
+            .map(|set| titles(&trie.guess(&mut vec![Some(set.nominative.clone())])))
+            .collect()
+    }
+
+    #[test]
+    fn built_from_dhall() {
+        let trie = crate::test_trie();
+        let sets = trie.gather();
+        assert!(!sets.is_empty());
+
+        for set in &sets {
+            let mut key = key_of(set).into_iter().map(Some).collect();
+            assert_eq!(titles(&trie.guess(&mut key)), vec![set.title()]);
+        }
+
+        // Sets that share a nominative share the top level, so it has fewer nodes than there
+        // are sets, and each of the five levels is balanced.
+        let balanced = (usize::BITS - sets.len().leading_zeros()) as usize;
+        assert!(trie.max_depth() <= 5 * balanced, "{}", trie.max_depth());
+    }
+
+    #[test]
+    fn inserting_in_order_stays_shallow() {
+        let mut trie = PronounTrie::build(vec![set("a0000")]);
+        for i in 1..1000 {
+            trie.insert(set(&format!("a{i:04}")));
+        }
+
+        assert_eq!(trie.gather().len(), 1000);
+        // Without rebalancing the top level would be a list 1000 nodes long. With it, the top
+        // level is at most twice as deep as a balanced one (log2(1000) is about 10), and the
+        // other four forms hang off it in a line.
+        assert!(trie.max_depth() <= 2 * 10 + 4, "{}", trie.max_depth());
+        assert_eq!(
+            titles(&trie.guess(&mut vec![Some("a0500".into())])),
+            vec!["a0500/a0500m"]
+        );
+    }
+
+    #[test]
+    fn guesses_survive_a_rebalance() {
+        let mut trie = crate::test_trie();
+        let sets = trie.gather();
+        let before = by_nominative(&trie, &sets);
+        let hir = titles(
+            &trie
+                .by_form("hir")
+                .into_iter()
+                .map(|found| found.set)
+                .collect::<Vec<_>>(),
+        );
+
+        // Sets that all sort after the real ones make the right side of the top level a long
+        // line until it is rebuilt.
+        let depth = trie.max_depth();
+        for i in 0..100 {
+            trie.insert(set(&format!("zz{i:03}")));
+        }
+        assert!(trie.max_depth() < depth + 100, "{}", trie.max_depth());
+
+        assert_eq!(by_nominative(&trie, &sets), before);
+        assert_eq!(
+            titles(
+                &trie
+                    .by_form("hir")
+                    .into_iter()
+                    .map(|found| found.set)
+                    .collect::<Vec<_>>()
+            ),
+            hir
+        );
+        assert_eq!(trie.gather().len(), sets.len() + 100);
+    }
+
+    fn key(url: &str) -> Vec<Option<String>> {
+        crate::url_to_trie_query(url)
+    }
+
+    #[test]
+    fn fuzzy_ranks_closest_first() {
+        let trie = crate::test_trie();
+
+        let found = trie.fuzzy(&key("thye/them"), 2);
+        assert_eq!(found[0].set.nominative, "they");
+        assert_eq!(found[0].distance, 1);
+        assert!(found
+            .windows(2)
+            .all(|pair| pair[0].distance <= pair[1].distance));
+
+        // An exact match costs nothing.
+        let found = trie.fuzzy(&key("she/her"), 2);
+        assert_eq!(found[0].set.title(), "she/her");
+        assert_eq!(found[0].distance, 0);
+    }
+
+    #[test]
+    fn fuzzy_stays_within_the_bound() {
+        let trie = crate::test_trie();
+
+        for max in 0..3 {
+            let found = trie.fuzzy(&key("shee/herr"), max);
+            assert!(found.iter().all(|found| found.distance <= max), "{max}");
+        }
+        assert!(trie.fuzzy(&key("shee/herr"), 1).is_empty());
+        assert_eq!(trie.fuzzy(&key("shee/herr"), 2)[0].set.title(), "she/her");
+        assert!(trie.fuzzy(&key("qwxzvk"), 2).is_empty());
+    }
+
+    #[test]
+    fn fuzzy_needs_a_form() {
+        let trie = crate::test_trie();
+
+        assert!(trie.fuzzy(&[], 2).is_empty());
+        assert!(trie.fuzzy(&key(""), 2).is_empty());
+        assert!(trie.fuzzy(&key(".../..."), 2).is_empty());
+    }
+
+    fn pairs(found: Vec<FormMatch>) -> Vec<(String, Case)> {
+        found
+            .into_iter()
+            .map(|found| (found.set.title(), found.case))
+            .collect()
+    }
+
+    #[test]
+    fn by_form_finds_every_case() {
+        let trie = crate::test_trie();
+
+        // Every set with "hir" in any case, worked out the slow way.
+        let expected: Vec<(String, Case)> = Case::ALL
+            .iter()
+            .flat_map(|&case| {
+                trie.gather()
+                    .into_iter()
+                    .filter(move |set| set.form(case) == "hir")
+                    .map(move |set| (set.title(), case))
+            })
+            .collect();
+        assert!(expected.iter().any(|(_, case)| *case == Case::Accusative));
+        assert!(expected.iter().any(|(_, case)| *case == Case::Determiner));
+
+        assert_eq!(pairs(trie.by_form("hir")), expected);
+        assert_eq!(pairs(trie.by_form(" HIR ")), expected);
+        assert!(trie.by_form("qwxzvk").is_empty());
+    }
+
+    #[test]
+    fn by_form_sees_inserted_sets() {
+        let mut trie = crate::test_trie();
+        trie.insert(set("hir"));
+
+        let found = pairs(trie.by_form("hir"));
+        assert_eq!(found[0], ("hir/hirm".to_string(), Case::Nominative));
+        assert_eq!(
+            found.len(),
+            pairs(crate::test_trie().by_form("hir")).len() + 1
+        );
+    }
+
+    #[test]
+    fn resolving() {
+        let trie = crate::test_trie();
+
+        assert_eq!(trie.resolve("she/her").unwrap().title(), "she/her");
+        assert_eq!(trie.resolve("They/Them").unwrap().nominative, "they");
+        assert_eq!(
+            trie.resolve("shee/her/her/hers/herself")
+                .unwrap()
+                .nominative,
+            "shee"
+        );
+        assert!(trie.resolve("shee/her").is_none());
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("she", "she", 2), Some(0));
+        assert_eq!(edit_distance("she", "he", 2), Some(1));
+        assert_eq!(edit_distance("thye", "they", 2), Some(1));
+        assert_eq!(edit_distance("", "he", 2), Some(2));
+        assert_eq!(edit_distance("kitten", "sitting", 3), Some(3));
+
+        assert_eq!(edit_distance("", "her", 2), None);
+        assert_eq!(edit_distance("kitten", "sitting", 2), None);
+        assert_eq!(edit_distance("she", "xe", 0), None);
+    }
 }
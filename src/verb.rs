@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+
+/// The grammatical number a verb has to agree with.
+#[derive(Clone, Copy, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Number {
+    #[default]
+    Singular,
+    Plural,
+}
+
+/// Verbs that don't follow the regular spelling rules. Each entry is the bare infinitive, the
+/// third person singular present and the third person plural present.
+const IRREGULAR: &[(&str, &str, &str)] = &[
+    ("be", "is", "are"),
+    ("have", "has", "have"),
+    ("do", "does", "do"),
+    ("go", "goes", "go"),
+];
+
+/// Change a conjugated verb that agrees with one number so that it agrees with another, like "is"
+/// to "are". Only the irregular verbs (and "was" and "were") are recognised, since telling a
+/// regular verb apart from any other word ending in "s" needs more than spelling.
+pub(crate) fn agree(word: &str, from: Number, to: Number) -> Option<&'static str> {
+    if from == to {
+        return None;
+    }
+
+    let word = word.to_lowercase();
+    let pick = |singular: &'static str, plural: &'static str| match (from, to) {
+        (Number::Singular, _) if word == singular => Some(plural),
+        (Number::Plural, _) if word == plural => Some(singular),
+        _ => None,
+    };
+
+    IRREGULAR
+        .iter()
+        .find_map(|(_, singular, plural)| pick(singular, plural))
+        .or_else(|| pick("was", "were"))
+}
+
+/// A verb in its bare infinitive form (e.g. "be", "throw", "carry").
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Verb<'a>(pub &'a str);
+
+impl<'a> Verb<'a> {
+    /// Conjugate the verb in the present tense so that it agrees with a third person subject of
+    /// the given number.
+    pub fn present(&self, number: Number) -> String {
+        if let Some((_, singular, plural)) = IRREGULAR.iter().find(|(base, _, _)| *base == self.0) {
+            return match number {
+                Number::Singular => singular.to_string(),
+                Number::Plural => plural.to_string(),
+            };
+        }
+
+        match number {
+            Number::Plural => self.0.to_string(),
+            Number::Singular => third_person_singular(self.0),
+        }
+    }
+
+    /// The contracted ending this verb takes after a pronoun of the given number, if it has one:
+    /// "be" gives "'s" or "'re", "have" gives "'s" or "'ve", "will" gives "'ll" and "would" and
+    /// "had" give "'d".
+    pub fn contraction(&self, number: Number) -> Option<&'static str> {
+        match (self.0, number) {
+            ("be", Number::Singular) | ("have", Number::Singular) => Some("'s"),
+            ("be", Number::Plural) => Some("'re"),
+            ("have", Number::Plural) => Some("'ve"),
+            ("will", _) => Some("'ll"),
+            ("would", _) | ("had", _) => Some("'d"),
+            _ => None,
+        }
+    }
+}
+
+/// Apply the regular English spelling rules for the third person singular present: "carry"
+/// becomes "carries", "watch" becomes "watches" and everything else gets an "s".
+fn third_person_singular(base: &str) -> String {
+    let vowel = |c: char| "aeiou".contains(c);
+
+    if let Some(stem) = base.strip_suffix('y') {
+        if stem.chars().last().is_some_and(|c| !vowel(c)) {
+            return format!("{stem}ies");
+        }
+    }
+
+    if ["s", "x", "z", "ch", "sh", "o"]
+        .iter()
+        .any(|suffix| base.ends_with(suffix))
+    {
+        return format!("{base}es");
+    }
+
+    format!("{base}s")
+}
+
+/// Undo [third_person_singular], like "carries" to "carry" or "watches" to "watch". Returns None
+/// if the word doesn't end in "s" the way a regular verb would. This only goes by spelling, so
+/// "dies" comes back as "dy".
+pub(crate) fn base_form(word: &str) -> Option<String> {
+    let vowel = |c: char| "aeiou".contains(c);
+
+    if let Some(stem) = word.strip_suffix("ies") {
+        if stem.chars().last().is_some_and(|c| !vowel(c)) {
+            return Some(format!("{stem}y"));
+        }
+    }
+
+    if let Some(stem) = word.strip_suffix("es") {
+        if ["ss", "x", "z", "ch", "sh", "o"]
+            .iter()
+            .any(|suffix| stem.ends_with(suffix))
+        {
+            return Some(stem.to_string());
+        }
+    }
+
+    word.strip_suffix('s')
+        .filter(|stem| !stem.is_empty() && !stem.ends_with(['s', 'u', 'i']))
+        .map(ToString::to_string)
+}
+
+/// The nominative of a set contracted with every verb that can be contracted onto it.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct Contractions {
+    /// "is" or "are", like "xe's" or "they're".
+    pub be: String,
+    /// "has" or "have", like "xe's" or "they've".
+    pub have: String,
+    /// "will", like "xe'll".
+    pub will: String,
+    /// "would" or "had", like "xe'd".
+    pub would: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PronounSet;
+
+    fn set(forms: &str) -> PronounSet {
+        let forms: Vec<String> = forms.split('/').map(ToString::to_string).collect();
+        PronounSet::new(
+            forms[0].clone(),
+            forms[1].clone(),
+            forms[2].clone(),
+            forms[3].clone(),
+            forms[4].clone(),
+        )
+    }
+
+    #[test]
+    fn contractions() {
+        for (forms, be, have, will, would) in [
+            (
+                "they/them/their/theirs/themselves",
+                "they're",
+                "they've",
+                "they'll",
+                "they'd",
+            ),
+            (
+                "they/them/their/theirs/themself",
+                "they're",
+                "they've",
+                "they'll",
+                "they'd",
+            ),
+            ("xe/xem/xyr/xyrs/xemself", "xe's", "xe's", "xe'll", "xe'd"),
+            (
+                "she/her/her/hers/herself",
+                "she's",
+                "she's",
+                "she'll",
+                "she'd",
+            ),
+        ] {
+            assert_eq!(
+                set(forms).contractions(),
+                Contractions {
+                    be: be.to_string(),
+                    have: have.to_string(),
+                    will: will.to_string(),
+                    would: would.to_string(),
+                },
+                "{forms}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_some_verbs_contract() {
+        let she = set("she/her/her/hers/herself");
+        assert_eq!(she.contraction("had"), Some("she'd".to_string()));
+        assert_eq!(she.contraction("go"), None);
+    }
+
+    #[test]
+    fn present() {
+        for (verb, singular, plural) in [
+            ("be", "is", "are"),
+            ("have", "has", "have"),
+            ("go", "goes", "go"),
+            ("carry", "carries", "carry"),
+            ("play", "plays", "play"),
+            ("watch", "watches", "watch"),
+            ("run", "runs", "run"),
+        ] {
+            assert_eq!(Verb(verb).present(Number::Singular), singular);
+            assert_eq!(Verb(verb).present(Number::Plural), plural);
+        }
+    }
+
+    #[test]
+    fn base_forms() {
+        for (verb, _, _) in IRREGULAR {
+            assert_eq!(base_form(verb), None);
+        }
+
+        for base in [
+            "carry", "play", "watch", "fix", "buzz", "miss", "run", "use", "wash",
+        ] {
+            assert_eq!(
+                base_form(&Verb(base).present(Number::Singular)).as_deref(),
+                Some(base)
+            );
+        }
+
+        assert_eq!(base_form("this"), None);
+        assert_eq!(base_form("bus"), None);
+        assert_eq!(base_form("s"), None);
+    }
+
+    #[test]
+    fn agreement() {
+        assert_eq!(agree("is", Number::Singular, Number::Plural), Some("are"));
+        assert_eq!(agree("Were", Number::Plural, Number::Singular), Some("was"));
+        assert_eq!(agree("is", Number::Plural, Number::Singular), None);
+        assert_eq!(agree("is", Number::Singular, Number::Singular), None);
+    }
+}
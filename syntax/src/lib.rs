@@ -0,0 +1,263 @@
+//! The placeholder syntax shared by `xe_pronouns::Template`, the `pronoun_format!` macro and
+//! the example sentences of other languages, so that they all accept the same templates.
+//!
+//! Placeholders are written in braces:
+//!
+//! - `{nom}`, `{acc}`, `{det}`, `{pos}` and `{ref}` (or `{nominative}`, `{accusative}`,
+//!   `{determiner}`, `{possessive}` and `{reflexive}`) insert that form.
+//! - `{nom+be}`, `{nom+have}`, `{nom+will}`, `{nom+would}` and `{nom+had}` insert the nominative
+//!   contracted with that verb, like "they're" or "xe's".
+//! - `{verb:go}` conjugates a verb to agree with the set, giving "goes" or "go".
+//! - `{to|between}` picks the first word for sets that refer to one person and the second for
+//!   sets that refer to a group.
+//!
+//! Capitalising a placeholder (`{Nom}`) capitalises what it inserts. Placeholders at the start
+//! of a sentence are capitalised either way. Write `{{` and `}}` for literal braces.
+
+use std::{error, fmt};
+
+/// The verbs that can be contracted onto a nominative.
+pub const CONTRACTIONS: &[&str] = &["be", "have", "will", "would", "had"];
+
+/// Why a template couldn't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A placeholder that isn't a case, verb or number marker, like `{nominitive}`.
+    UnknownPlaceholder(String),
+    /// A contraction with a verb that doesn't contract, like `{nom+go}`.
+    UnknownContraction(String),
+    /// A verb or number marker with something missing, like `{verb:}` or `{a|}`.
+    Incomplete(String),
+    /// A `{` at this byte offset that is never closed.
+    Unclosed(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownPlaceholder(name) => write!(f, "unknown placeholder {{{name}}}"),
+            Error::UnknownContraction(name) => write!(f, "{{{name}}} is not a contraction"),
+            Error::Incomplete(name) if name.starts_with("verb:") => {
+                write!(f, "{{{name}}} needs a verb like {{verb:go}}")
+            }
+            Error::Incomplete(name) => write!(f, "{{{name}}} needs two words like {{to|between}}"),
+            Error::Unclosed(at) => write!(f, "unclosed placeholder at byte {at}"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// One piece of a template before the placeholders are looked at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// Text with `{{` and `}}` already turned into single braces.
+    Text(String),
+    /// What is between the braces of a placeholder.
+    Placeholder {
+        name: &'a str,
+        /// Whether the placeholder starts a sentence: it is at the beginning of the template or
+        /// after a ".", "!" or "?" followed by whitespace.
+        sentence_start: bool,
+    },
+}
+
+impl Token<'_> {
+    /// Whether what the placeholder inserts should be capitalised, because it starts a sentence
+    /// or its name is capitalised.
+    pub fn capitalised(&self) -> bool {
+        match self {
+            Token::Text(_) => false,
+            Token::Placeholder {
+                name,
+                sentence_start,
+            } => *sentence_start || name.starts_with(char::is_uppercase),
+        }
+    }
+}
+
+/// Split a template into text and placeholders, without checking what the placeholders are.
+pub fn tokenize(source: &str) -> Result<Vec<Token<'_>>, Error> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = source;
+    let mut sentence_start = true;
+
+    while let Some(at) = rest.find(['{', '}']) {
+        text.push_str(&rest[..at]);
+        let offset = source.len() - rest.len() + at;
+
+        if rest[at..].starts_with("{{") || rest[at..].starts_with("}}") {
+            text.push_str(&rest[at..at + 1]);
+            rest = &rest[at + 2..];
+            continue;
+        }
+
+        if rest[at..].starts_with('}') {
+            text.push('}');
+            rest = &rest[at + 1..];
+            continue;
+        }
+
+        let end = rest[at..].find('}').ok_or(Error::Unclosed(offset))?;
+
+        if !text.is_empty() {
+            let trimmed = text.trim_end();
+            if !trimmed.is_empty() {
+                sentence_start = trimmed.ends_with(['.', '!', '?']) && trimmed.len() < text.len();
+            }
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+        }
+
+        tokens.push(Token::Placeholder {
+            name: &rest[at + 1..at + end],
+            sentence_start,
+        });
+        sentence_start = false;
+
+        rest = &rest[at + end + 1..];
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    Ok(tokens)
+}
+
+/// One of the five forms of an English pronoun set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Case {
+    Nominative,
+    Accusative,
+    Determiner,
+    Possessive,
+    Reflexive,
+}
+
+impl Case {
+    /// Look up a case by its name or abbreviation in a placeholder, ignoring capitalisation.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "nom" | "nominative" => Some(Case::Nominative),
+            "acc" | "accusative" => Some(Case::Accusative),
+            "det" | "determiner" => Some(Case::Determiner),
+            "pos" | "possessive" => Some(Case::Possessive),
+            "ref" | "reflexive" => Some(Case::Reflexive),
+            _ => None,
+        }
+    }
+}
+
+/// What an English placeholder asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placeholder<'a> {
+    /// A pronoun form.
+    Form(Case),
+    /// The nominative contracted with a verb from [CONTRACTIONS].
+    Contraction(&'a str),
+    /// A verb in its bare infinitive, to be conjugated to agree with the set.
+    Verb(&'a str),
+    /// Two alternatives, the first for sets that refer to one person and the second for sets
+    /// that refer to a group.
+    Number(&'a str, &'a str),
+}
+
+impl<'a> Placeholder<'a> {
+    /// Work out what a placeholder (without its braces) asks for.
+    pub fn parse(name: &'a str) -> Result<Self, Error> {
+        let incomplete = || Error::Incomplete(name.to_string());
+
+        if let Some(verb) = name.strip_prefix("verb:") {
+            if verb.is_empty() || !verb.chars().all(char::is_alphabetic) {
+                return Err(incomplete());
+            }
+            return Ok(Placeholder::Verb(verb));
+        }
+
+        if let Some((one, many)) = name.split_once('|') {
+            if one.is_empty() || many.is_empty() {
+                return Err(incomplete());
+            }
+            return Ok(Placeholder::Number(one, many));
+        }
+
+        let (case, verb) = match name.split_once('+') {
+            Some((case, verb)) => (case, Some(verb)),
+            None => (name, None),
+        };
+        let case =
+            Case::from_name(case).ok_or_else(|| Error::UnknownPlaceholder(name.to_string()))?;
+
+        match verb {
+            None => Ok(Placeholder::Form(case)),
+            Some(verb) if case == Case::Nominative && CONTRACTIONS.contains(&verb) => {
+                Ok(Placeholder::Contraction(verb))
+            }
+            Some(_) => Err(Error::UnknownContraction(name.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholder(name: &str, sentence_start: bool) -> Token<'_> {
+        Token::Placeholder {
+            name,
+            sentence_start,
+        }
+    }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            tokenize("{Nom} left. {nom}! {{x}}").unwrap(),
+            vec![
+                placeholder("Nom", true),
+                Token::Text(" left. ".into()),
+                placeholder("nom", true),
+                Token::Text("! {x}".into()),
+            ]
+        );
+        assert_eq!(
+            tokenize("a.b {nom}{acc}").unwrap(),
+            vec![
+                Token::Text("a.b ".into()),
+                placeholder("nom", false),
+                placeholder("acc", false),
+            ]
+        );
+        assert_eq!(tokenize("ok {nom"), Err(Error::Unclosed(3)));
+    }
+
+    #[test]
+    fn placeholders() {
+        assert_eq!(
+            Placeholder::parse("Determiner"),
+            Ok(Placeholder::Form(Case::Determiner))
+        );
+        assert_eq!(
+            Placeholder::parse("nom+be"),
+            Ok(Placeholder::Contraction("be"))
+        );
+        assert_eq!(Placeholder::parse("verb:go"), Ok(Placeholder::Verb("go")));
+        assert_eq!(
+            Placeholder::parse("to|between"),
+            Ok(Placeholder::Number("to", "between"))
+        );
+
+        for (name, error) in [
+            ("nominitive", Error::UnknownPlaceholder("nominitive".into())),
+            ("nom+go", Error::UnknownContraction("nom+go".into())),
+            ("acc+be", Error::UnknownContraction("acc+be".into())),
+            ("verb:", Error::Incomplete("verb:".into())),
+            ("|", Error::Incomplete("|".into())),
+            ("a|", Error::Incomplete("a|".into())),
+        ] {
+            assert_eq!(Placeholder::parse(name), Err(error), "{name}");
+        }
+    }
+}